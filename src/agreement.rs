@@ -0,0 +1,302 @@
+//! Statistics of the agreement between the judges of a panel.
+//!
+//! The judgements of a panel are a table with one row per performance and one
+//! column per judge. For each category, and for the score, the agreement is
+//! measured with:
+//!
+//! - the exact-agreement rate: the share of performances on which every
+//!   judge gives the same value;
+//! - the mean absolute difference between the values of two judges, over
+//!   every pair of judges and every performance;
+//! - Cohen's kappa, for two judges, or the mean of Cohen's kappa over every
+//!   pair of judges (Light's kappa) for more;
+//! - Fleiss' kappa;
+//! - the intraclass correlation coefficient ICC(2,1): two-way random effects,
+//!   absolute agreement, single judge.
+//!
+//! The kappas treat every value as a separate class, and are undefined when
+//! every judge always gives the same value. The ICC is undefined with a
+//! single performance or when there is no variance at all.
+//!
+//! The bias of a judge is the mean difference between their values and the
+//! mean of the panel on the same performance.
+//!
+//! The statistics serialize with the same field names, and the categories as
+//! maps from their names to their values; undefined statistics are `null`.
+
+use std::collections::HashMap;
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+
+use crate::error::AgreementError;
+use crate::profile::ScoringProfile;
+use crate::score::ScorePolicy;
+use crate::style_code::{Category, StyleCode};
+
+/// The agreement of a panel on one category, or on the score.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Agreement {
+  /// The share of performances on which every judge gives the same value,
+  /// between 0 and 1.
+  pub exact: f64,
+  /// The mean absolute difference between the values of two judges.
+  pub mean_absolute_difference: f64,
+  /// Cohen's kappa, or the mean of the pairwise Cohen's kappas with more than
+  /// two judges. `None` if undefined.
+  pub cohen_kappa: Option<f64>,
+  /// Fleiss' kappa. `None` if undefined.
+  pub fleiss_kappa: Option<f64>,
+  /// The intraclass correlation coefficient ICC(2,1). `None` if undefined.
+  pub icc: Option<f64>,
+}
+
+/// The bias of one judge relative to the mean of the panel.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JudgeBias {
+  /// The bias on each category, in the order of [`Category::ALL`].
+  #[serde(serialize_with = "serialize_by_category")]
+  pub categories: Vec<(Category, f64)>,
+  /// The bias on the score.
+  pub score: f64,
+}
+
+/// The agreement statistics of a panel.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AgreementReport {
+  /// The number of performances.
+  pub performances: usize,
+  /// The number of judges.
+  pub judges: usize,
+  /// The agreement on each category, in the order of [`Category::ALL`].
+  #[serde(serialize_with = "serialize_by_category")]
+  pub categories: Vec<(Category, Agreement)>,
+  /// The agreement on the score.
+  pub score: Agreement,
+  /// The bias of each judge, in the order of the judgements.
+  pub bias: Vec<JudgeBias>,
+}
+
+/// Measures the agreement of a panel. Each element of `panels` holds the
+/// judgements of one performance, in the same order of judges.
+///
+/// Scores are computed with the weights of `profile`, without raising or
+/// rejecting the scores below the floor.
+pub fn measure_agreement(
+  panels: &[Vec<StyleCode>],
+  profile: &ScoringProfile,
+) -> Result<AgreementReport, AgreementError> {
+  let judges = match panels.first() {
+    Some(panel) => panel.len(),
+    None => return Err(AgreementError::NoPerformances),
+  };
+  if judges < 2 {
+    return Err(AgreementError::TooFewJudges(judges));
+  }
+  if let Some((performance, panel)) = panels
+    .iter()
+    .enumerate()
+    .find(|(_, panel)| panel.len() != judges)
+  {
+    return Err(AgreementError::MissingJudgements {
+      performance,
+      judgements: panel.len(),
+      judges,
+    });
+  }
+
+  let profile = profile
+    .clone()
+    .with_score_policy(ScorePolicy::AllowNegative);
+  // Scores are compared in tenths, so that they are exact.
+  let scores: Vec<Vec<i64>> = panels
+    .iter()
+    .map(|panel| {
+      panel
+        .iter()
+        .map(|style| i64::from(style.score_with(&profile).unwrap().tenths()))
+        .collect()
+    })
+    .collect();
+  let values = |category: Category| -> Vec<Vec<i64>> {
+    panels
+      .iter()
+      .map(|panel| {
+        panel
+          .iter()
+          .map(|style| i64::from(style.get(category)))
+          .collect()
+      })
+      .collect()
+  };
+
+  let mut score = measure(&scores);
+  score.mean_absolute_difference /= 10.0;
+  let score_bias: Vec<f64> = bias(&scores).into_iter().map(|b| b / 10.0).collect();
+  let category_bias: Vec<(Category, Vec<f64>)> = Category::ALL
+    .iter()
+    .map(|&category| (category, bias(&values(category))))
+    .collect();
+
+  Ok(AgreementReport {
+    performances: panels.len(),
+    judges,
+    categories: Category::ALL
+      .iter()
+      .map(|&category| (category, measure(&values(category))))
+      .collect(),
+    score,
+    bias: (0..judges)
+      .map(|judge| JudgeBias {
+        categories: category_bias
+          .iter()
+          .map(|(category, bias)| (*category, bias[judge]))
+          .collect(),
+        score: score_bias[judge],
+      })
+      .collect(),
+  })
+}
+
+/// Measures the agreement on a table of values with one row per performance
+/// and one column per judge.
+fn measure(rows: &[Vec<i64>]) -> Agreement {
+  let n = rows.len() as f64;
+  let judges = rows[0].len();
+  let pairs = (0..judges).flat_map(|a| (a + 1..judges).map(move |b| (a, b)));
+
+  let exact = rows
+    .iter()
+    .filter(|row| row.iter().all(|&v| v == row[0]))
+    .count() as f64
+    / n;
+  let differences: Vec<f64> = pairs
+    .clone()
+    .flat_map(|(a, b)| rows.iter().map(move |row| (row[a] - row[b]).abs() as f64))
+    .collect();
+  let mean_absolute_difference = differences.iter().sum::<f64>() / differences.len() as f64;
+
+  let kappas: Vec<f64> = pairs.filter_map(|(a, b)| cohen_kappa(rows, a, b)).collect();
+  let cohen_kappa = if kappas.is_empty() {
+    None
+  } else {
+    Some(kappas.iter().sum::<f64>() / kappas.len() as f64)
+  };
+
+  Agreement {
+    exact,
+    mean_absolute_difference,
+    cohen_kappa,
+    fleiss_kappa: fleiss_kappa(rows),
+    icc: icc(rows),
+  }
+}
+
+/// `(observed - expected) / (1 - expected)`, or `None` if the expected
+/// agreement is complete.
+fn kappa(observed: f64, expected: f64) -> Option<f64> {
+  if 1.0 - expected < 1e-12 {
+    None
+  } else {
+    Some((observed - expected) / (1.0 - expected))
+  }
+}
+
+/// Cohen's kappa between judges `a` and `b`.
+fn cohen_kappa(rows: &[Vec<i64>], a: usize, b: usize) -> Option<f64> {
+  let n = rows.len() as f64;
+  let observed = rows.iter().filter(|row| row[a] == row[b]).count() as f64 / n;
+  let mut counts: HashMap<i64, (f64, f64)> = HashMap::new();
+  for row in rows {
+    counts.entry(row[a]).or_default().0 += 1.0;
+    counts.entry(row[b]).or_default().1 += 1.0;
+  }
+  let expected = counts.values().map(|(a, b)| a / n * (b / n)).sum();
+  kappa(observed, expected)
+}
+
+/// Fleiss' kappa of all the judges.
+fn fleiss_kappa(rows: &[Vec<i64>]) -> Option<f64> {
+  let n = rows.len() as f64;
+  let judges = rows[0].len() as f64;
+  let mut totals: HashMap<i64, f64> = HashMap::new();
+  let mut observed = 0.0;
+  for row in rows {
+    let mut counts: HashMap<i64, f64> = HashMap::new();
+    for &value in row {
+      *counts.entry(value).or_default() += 1.0;
+      *totals.entry(value).or_default() += 1.0;
+    }
+    let agreeing_pairs = counts.values().map(|c| c * (c - 1.0)).sum::<f64>();
+    observed += agreeing_pairs / (judges * (judges - 1.0));
+  }
+  let expected = totals
+    .values()
+    .map(|total| (total / (n * judges)).powi(2))
+    .sum();
+  kappa(observed / n, expected)
+}
+
+/// The intraclass correlation coefficient ICC(2,1), from a two-way analysis
+/// of variance.
+fn icc(rows: &[Vec<i64>]) -> Option<f64> {
+  let n = rows.len();
+  let k = rows[0].len();
+  if n < 2 {
+    return None;
+  }
+  let (nf, kf) = (n as f64, k as f64);
+  let grand_mean = rows.iter().flatten().sum::<i64>() as f64 / (nf * kf);
+  let row_means: Vec<f64> = rows
+    .iter()
+    .map(|row| row.iter().sum::<i64>() as f64 / kf)
+    .collect();
+  let column_means: Vec<f64> = (0..k)
+    .map(|j| rows.iter().map(|row| row[j]).sum::<i64>() as f64 / nf)
+    .collect();
+
+  let squares = |values: &mut dyn Iterator<Item = f64>| -> f64 {
+    values.map(|v| (v - grand_mean).powi(2)).sum()
+  };
+  let total = squares(&mut rows.iter().flatten().map(|&v| v as f64));
+  let between_rows = kf * squares(&mut row_means.iter().copied());
+  let between_columns = nf * squares(&mut column_means.iter().copied());
+  let error = total - between_rows - between_columns;
+
+  let ms_rows = between_rows / (nf - 1.0);
+  let ms_columns = between_columns / (kf - 1.0);
+  let ms_error = error / ((nf - 1.0) * (kf - 1.0));
+  let denominator = ms_rows + (kf - 1.0) * ms_error + kf * (ms_columns - ms_error) / nf;
+  if denominator.abs() < 1e-12 {
+    None
+  } else {
+    Some((ms_rows - ms_error) / denominator)
+  }
+}
+
+/// The mean difference between the values of each judge and the mean of the
+/// panel.
+fn bias(rows: &[Vec<i64>]) -> Vec<f64> {
+  let n = rows.len() as f64;
+  let judges = rows[0].len();
+  let mut bias = vec![0.0; judges];
+  for row in rows {
+    let mean = row.iter().sum::<i64>() as f64 / judges as f64;
+    for (judge, &value) in row.iter().enumerate() {
+      bias[judge] += (value as f64 - mean) / n;
+    }
+  }
+  bias
+}
+
+/// Serializes values by category as a map from the names of the categories.
+fn serialize_by_category<T: Serialize, S: Serializer>(
+  values: &[(Category, T)],
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  let mut map = serializer.serialize_map(Some(values.len()))?;
+  for (category, value) in values {
+    map.serialize_entry(category.name(), value)?;
+  }
+  map.end()
+}
@@ -0,0 +1,105 @@
+//! Analysis of how robust codes are to typos.
+//!
+//! A single mistyped character can turn a code into another valid code, with
+//! a different judgement and score. The analysis counts, for a code, the
+//! single-character edits (substitutions with any other letter or digit, and
+//! swaps of two adjacent characters) that yield another valid code, and how
+//! much they change the score.
+
+use serde::Serialize;
+
+use crate::profile::ScoringProfile;
+use crate::score::{Score, ScorePolicy};
+use crate::style_code::{Layout, StyleCode, ALPHABET_36};
+
+/// The typo robustness of one code.
+///
+/// It serializes with the same field names, the layout as its number of
+/// letters and the scores as numbers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodeAnalysis {
+  /// The analyzed code.
+  pub code: String,
+  /// The layout of the code.
+  pub layout: Layout,
+  /// The score of the code.
+  pub score: Score,
+  /// The number of single-character edits of the code.
+  pub edits: u32,
+  /// The number of edits that yield a valid code with a different judgement.
+  pub valid_edits: u32,
+  /// The largest score change caused by one of these edits.
+  pub max_score_change: Score,
+  /// The total score change caused by these edits, for computing the mean.
+  pub total_score_change: Score,
+}
+
+impl CodeAnalysis {
+  /// The mean score change caused by the edits that yield a valid code, or
+  /// zero if there are none.
+  pub fn mean_score_change(&self) -> f64 {
+    if self.valid_edits == 0 {
+      0.0
+    } else {
+      self.total_score_change.as_f64() / f64::from(self.valid_edits)
+    }
+  }
+}
+
+/// Analyzes every single-character edit of `code`.
+///
+/// Edited codes count as valid if they decode to a judgement within the
+/// limits of `profile`. Scores are compared without applying the score
+/// policy of the profile, so that changes below the floor are not hidden.
+///
+/// # Panics
+///
+/// Panics if `code` is not a valid code.
+pub fn analyze_code(code: &str, profile: &ScoringProfile) -> CodeAnalysis {
+  let profile = profile
+    .clone()
+    .with_score_policy(ScorePolicy::AllowNegative);
+  let (style, layout) = StyleCode::decode_with_layout(code).expect("invalid code");
+  let score = style.score_with(&profile).unwrap();
+  let mut analysis = CodeAnalysis {
+    code: code.to_string(),
+    layout,
+    score,
+    edits: 0,
+    valid_edits: 0,
+    max_score_change: Score::ZERO,
+    total_score_change: Score::ZERO,
+  };
+
+  let chars: Vec<char> = code.chars().map(|c| c.to_ascii_lowercase()).collect();
+  let mut check = |edited: &[char]| {
+    analysis.edits += 1;
+    let other = match StyleCode::decode_lowercase(edited) {
+      Ok((other, _)) if other != style && profile.validate(&other).is_ok() => other,
+      _ => return,
+    };
+    let change = other.score_with(&profile).unwrap() - score;
+    let change = change.max(-change);
+    analysis.valid_edits += 1;
+    analysis.max_score_change = analysis.max_score_change.max(change);
+    analysis.total_score_change += change;
+  };
+
+  let mut edited = chars.clone();
+  for i in 0..chars.len() {
+    for c in ALPHABET_36.chars().filter(|&c| c != chars[i]) {
+      edited[i] = c;
+      check(&edited);
+    }
+    edited[i] = chars[i];
+  }
+  for i in 0..chars.len().saturating_sub(1) {
+    if chars[i] != chars[i + 1] {
+      edited.swap(i, i + 1);
+      check(&edited);
+      edited.swap(i, i + 1);
+    }
+  }
+
+  analysis
+}
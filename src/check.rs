@@ -0,0 +1,74 @@
+//! Check characters, to detect transcription errors in codes.
+//!
+//! A checked code is a Style Code followed by `-` and a check character, e.g.
+//! `g13dm12-b`. The check character is computed with the ISO 7064 MOD 37,36
+//! hybrid system over the letters and digits of the code, so that any single
+//! wrong character and any swap of two adjacent characters is detected.
+//!
+//! ```
+//! use servizio::{check_character, with_check, StyleCode};
+//!
+//! assert_eq!(check_character("g13dm12"), Some('b'));
+//! assert_eq!(with_check("g13dm12"), Some("g13dm12-b".to_string()));
+//! assert!(StyleCode::decode("g13dm12-b").is_ok());
+//! ```
+
+use crate::error::DecodeError;
+use crate::style_code::ALPHABET_36;
+
+/// The separator between a code and its check character.
+pub const CHECK_SEPARATOR: char = '-';
+
+/// Computes the check character of `code` (case insensitive).
+///
+/// Returns `None` if the code contains characters other than ASCII letters
+/// and digits.
+pub fn check_character(code: &str) -> Option<char> {
+  let modulus = 36;
+  let mut product = modulus;
+  for c in code.chars() {
+    let value = ALPHABET_36.find(c.to_ascii_lowercase())? as u32;
+    let mut sum = (product + value) % modulus;
+    if sum == 0 {
+      sum = modulus;
+    }
+    product = (sum * 2) % (modulus + 1);
+  }
+  let check = (modulus + 1 - product) % modulus;
+  ALPHABET_36.chars().nth(check as usize)
+}
+
+/// Appends the check separator and the check character to `code`.
+///
+/// Returns `None` if the code contains characters other than ASCII letters
+/// and digits.
+pub fn with_check(code: &str) -> Option<String> {
+  check_character(code).map(|check| format!("{}{}{}", code, CHECK_SEPARATOR, check))
+}
+
+/// Verifies the check character of a lowercase code, whose separator is at
+/// `separator`.
+pub(crate) fn verify(chars: &[char], separator: usize) -> Result<(), DecodeError> {
+  let offset = separator + 1;
+  let found = match chars.get(offset) {
+    Some(&found) => found,
+    None => return Err(DecodeError::MissingCheckCharacter { offset }),
+  };
+  if let Some(&c) = chars.get(offset + 1) {
+    return Err(DecodeError::TrailingCharacters {
+      offset: offset + 1,
+      found: c,
+    });
+  }
+  let code: String = chars[..separator].iter().collect();
+  // The code was decoded, so it only holds letters and digits.
+  let expected = check_character(&code).unwrap();
+  if found != expected {
+    return Err(DecodeError::CheckMismatch {
+      offset,
+      found,
+      expected,
+    });
+  }
+  Ok(())
+}
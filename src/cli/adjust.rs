@@ -0,0 +1,156 @@
+//! The `adjust` subcommand.
+
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
+
+use servizio::{with_check, Category, OutputFormat, Record, RecordWriter, Score, CHECK_SEPARATOR};
+
+use crate::cli::{format_arg, output_format, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    let names = Category::ALL.iter().map(|c| c.name()).collect::<Vec<_>>();
+    SubCommand::with_name("adjust")
+        .about("Adjusts the categories of a Style Code, e.g. --pen=+1 --mov=-1, and prints the new code")
+        .arg(
+            Arg::with_name("code")
+                .help("The Style Code to adjust")
+                .required(true),
+        )
+        .args(
+            &Category::ALL
+                .iter()
+                .map(|category| {
+                    Arg::with_name(category.name())
+                        .help("The points to add to the category, or to remove if negative")
+                        .long(category.name())
+                        .takes_value(true)
+                        .allow_hyphen_values(true)
+                })
+                .collect::<Vec<_>>(),
+        )
+        .group(
+            ArgGroup::with_name("deltas")
+                .args(&names)
+                .multiple(true)
+                .required(true),
+        )
+        .arg(format_arg())
+}
+
+/// Runs the subcommand, returning the exit code.
+///
+/// In raw mode, only the new code is printed.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let format = output_format(matches);
+    // Machine-readable formats only print the records on standard output.
+    let settings = &Settings {
+        raw: settings.raw || format != OutputFormat::Text,
+        ..settings.clone()
+    };
+
+    let code = matches.value_of("code").unwrap();
+    let before = match settings.profile.decode(code) {
+        Ok(style) => style,
+        Err(err) => {
+            settings.invalid_code(&format!("Invalid style code: {}.", err), code);
+            return 1;
+        }
+    };
+
+    let mut deltas = Vec::new();
+    for &category in Category::ALL.iter() {
+        if let Some(delta) = matches.value_of(category.name()) {
+            match delta.strip_prefix('+').unwrap_or(delta).parse::<i32>() {
+                Ok(delta) => deltas.push((category, delta)),
+                Err(_) => {
+                    settings.error(&format!(
+                        "Invalid adjustment: invalid delta '{}' for {}.",
+                        delta, category
+                    ));
+                    return 2;
+                }
+            }
+        }
+    }
+    let adjusted = before.adjust(&deltas).and_then(|after| {
+        let new_code = settings.profile.encode(&after)?;
+        Ok((after, new_code))
+    });
+    let (after, mut new_code) = match adjusted {
+        Ok(adjusted) => adjusted,
+        Err(err) => {
+            settings.error(&format!("Invalid adjustment: {}.", err));
+            return 2;
+        }
+    };
+    let scores = before
+        .score_with(&settings.profile)
+        .and_then(|before| Ok((before, after.score_with(&settings.profile)?)));
+    let (score_before, score_after) = match scores {
+        Ok(scores) => scores,
+        Err(err) => return settings.invalid_score(&err),
+    };
+
+    // A code with a check character is adjusted into one with a check
+    // character.
+    if code.contains(CHECK_SEPARATOR) {
+        new_code = with_check(&new_code).unwrap();
+    }
+
+    if format != OutputFormat::Text {
+        let record = Record {
+            code: new_code,
+            layout: after.layout(),
+            style: after,
+            score: score_after,
+        };
+        let mut writer =
+            RecordWriter::new(std::io::stdout(), format, settings.score_format.clone());
+        if writer.write(&record).and_then(|_| writer.finish()).is_err() {
+            return 1;
+        }
+        return 0;
+    }
+    if settings.raw {
+        println!("{}", new_code);
+        return 0;
+    }
+
+    let score_format = &settings.score_format;
+    println!("{:<6} {:>10} {:>10}", "", "Before", "After");
+    println!(
+        "{:<6} {:>10} {:>10}",
+        "Code",
+        code.to_ascii_lowercase(),
+        new_code
+    );
+    for &category in Category::ALL.iter() {
+        let (old, new) = (before.get(category), after.get(category));
+        let change = if old == new {
+            String::new()
+        } else {
+            format!("  ({:+})", i64::from(new) - i64::from(old))
+        };
+        println!(
+            "{:<6} {:>10} {:>10}{}",
+            category.to_string(),
+            old,
+            new,
+            change
+        );
+    }
+    let change = score_after - score_before;
+    println!(
+        "{:<6} {:>10} {:>10}{}",
+        "Score",
+        score_before.format(score_format),
+        score_after.format(score_format),
+        if change == Score::ZERO {
+            String::new()
+        } else if change > Score::ZERO {
+            format!("  (+{})", change.format(score_format))
+        } else {
+            format!("  ({})", change.format(score_format))
+        }
+    );
+    0
+}
@@ -0,0 +1,187 @@
+//! The `agreement` subcommand.
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use serde::Serialize;
+
+use servizio::{measure_agreement, read_panels, AgreementReport, Category};
+
+use crate::cli::{file_error_code, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("agreement")
+        .about("Measures how consistent the judges of a panel are, from a csv file with one row per performance and one code column per judge")
+        .arg(
+            Arg::with_name("infile")
+                .help("The input file")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("headers")
+                .help("Treat the first line as column headers, naming the judges")
+                .long("headers"),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .help("The indexes of the columns with the codes of the judges (zero-based), separated by commas. Default is every column but the first")
+                .long("columns")
+                .takes_value(true)
+                .validator(|columns| match parse_columns(&columns) {
+                    Some(_) => Ok(()),
+                    None => Err(format!("invalid columns '{}'", columns)),
+                }),
+        )
+        .arg(
+            Arg::with_name("delimiter")
+                .help("Specifies the delimiter in the CSV file. Default is comma")
+                .long("delimiter")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("The output format. Default is text")
+                .long("format")
+                .short("f")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .case_insensitive(true),
+        )
+}
+
+fn parse_columns(columns: &str) -> Option<Vec<usize>> {
+    columns
+        .split(',')
+        .map(|column| column.trim().parse().ok())
+        .collect()
+}
+
+/// Formats a statistic that may be undefined.
+fn optional(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |value| format!("{:.2}", value))
+}
+
+/// The JSON report: the statistics, with the names of the judges, in the
+/// order of the bias, and the number of skipped records.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    #[serde(flatten)]
+    report: &'a AgreementReport,
+    names: &'a [String],
+    skipped: usize,
+}
+
+/// Runs the subcommand, returning the exit code.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let json = matches
+        .value_of("format")
+        .is_some_and(|f| f.eq_ignore_ascii_case("json"));
+    // JSON only prints the report on standard output.
+    let settings = &Settings {
+        raw: settings.raw || json,
+        ..settings.clone()
+    };
+
+    let delimiter = match matches.value_of("delimiter") {
+        Some(d) => {
+            let bytes = &d.as_bytes();
+            if bytes.len() != 1 {
+                settings.error("Invalid delimiter");
+                return 4;
+            }
+            bytes[0] as char
+        }
+        None => ',',
+    };
+    let columns = matches.value_of("columns").and_then(parse_columns);
+
+    let file = match read_panels(
+        matches.value_of("infile").unwrap(),
+        delimiter,
+        matches.is_present("headers"),
+        columns.as_deref(),
+        &settings.profile,
+    ) {
+        Ok(file) => file,
+        Err(err) => {
+            settings.error(&format!("An error occurred: {}.", err));
+            return file_error_code(&err);
+        }
+    };
+    for (line, reason) in &file.skipped {
+        settings.error(&format!("Skipped line {}: {}.", line, reason));
+    }
+    let report = match measure_agreement(&file.panels, &settings.profile) {
+        Ok(report) => report,
+        Err(err) => {
+            settings.error(&format!("Cannot measure the agreement: {}.", err));
+            return 1;
+        }
+    };
+
+    if json {
+        let json = JsonReport {
+            report: &report,
+            names: &file.judges,
+            skipped: file.skipped.len(),
+        };
+        println!("{}", serde_json::to_string(&json).unwrap());
+        return 0;
+    }
+
+    println!(
+        "{} performances ({} skipped), {} judges.",
+        report.performances,
+        file.skipped.len(),
+        report.judges
+    );
+    println!(
+        "{:<8} {:>7} {:>6} {:>6} {:>6} {:>6}",
+        "", "Exact", "MAD", "Cohen", "Fleiss", "ICC"
+    );
+    let rows = report
+        .categories
+        .iter()
+        .map(|(category, agreement)| (category.to_string(), agreement))
+        .chain(std::iter::once(("Score".to_string(), &report.score)));
+    for (name, agreement) in rows {
+        println!(
+            "{:<8} {:>6.1}% {:>6.2} {:>6} {:>6} {:>6}",
+            name,
+            100.0 * agreement.exact,
+            agreement.mean_absolute_difference,
+            optional(agreement.cohen_kappa),
+            optional(agreement.fleiss_kappa),
+            optional(agreement.icc)
+        );
+    }
+
+    println!();
+    println!("Bias relative to the panel mean:");
+    let width = file
+        .judges
+        .iter()
+        .map(|judge| judge.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    let header = Category::ALL
+        .iter()
+        .map(|category| format!(" {:>6}", category.to_string()))
+        .collect::<String>();
+    println!("{:<width$}{} {:>6}", "", header, "Score", width = width);
+    for (judge, bias) in file.judges.iter().zip(report.bias.iter()) {
+        let values = bias
+            .categories
+            .iter()
+            .map(|(_, bias)| format!(" {:>+6.2}", bias))
+            .collect::<String>();
+        println!(
+            "{:<width$}{} {:>+6.2}",
+            judge,
+            values,
+            bias.score,
+            width = width
+        );
+    }
+    0
+}
@@ -0,0 +1,202 @@
+//! The `analyze` subcommand.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::thread;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::{analyze_code, with_check, CodeAnalysis, Layout, Score, StyleCode};
+
+use crate::cli::Settings;
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("analyze")
+        .about(
+            "Analyzes how many single-character typos turn each valid code into another valid code",
+        )
+        .arg(
+            Arg::with_name("report")
+                .help("Write the analysis of each code to this file")
+                .long("report")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("The format of the report. Default is csv")
+                .long("format")
+                .short("f")
+                .takes_value(true)
+                .possible_values(&["csv", "json"])
+                .case_insensitive(true)
+                .requires("report"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .help("Analyze the codes with a check character")
+                .long("check"),
+        )
+}
+
+/// The number of codes analyzed between two writes of the report.
+const BATCH_SIZE: usize = 1 << 16;
+
+/// Totals of the analyses of a group of codes.
+#[derive(Default)]
+struct Summary {
+    codes: u64,
+    edits: u64,
+    valid_edits: u64,
+    codes_with_valid_edits: u64,
+    max_score_change: Score,
+    total_score_change: i64,
+}
+
+impl Summary {
+    fn add(&mut self, analysis: &CodeAnalysis) {
+        self.codes += 1;
+        self.edits += u64::from(analysis.edits);
+        self.valid_edits += u64::from(analysis.valid_edits);
+        if analysis.valid_edits > 0 {
+            self.codes_with_valid_edits += 1;
+        }
+        self.max_score_change = self.max_score_change.max(analysis.max_score_change);
+        self.total_score_change += i64::from(analysis.total_score_change.tenths());
+    }
+
+    fn print_row(&self, name: &str, settings: &Settings) {
+        let percent = |part: u64, total: u64| {
+            if total == 0 {
+                0.0
+            } else {
+                100.0 * part as f64 / total as f64
+            }
+        };
+        let mean_change = if self.valid_edits == 0 {
+            0.0
+        } else {
+            self.total_score_change as f64 / 10.0 / self.valid_edits as f64
+        };
+        println!(
+            "{:<8} {:>8} {:>9} {:>12} {:>7.2}% {:>12.1}% {:>11.2} {:>10}",
+            name,
+            self.codes,
+            self.edits,
+            self.valid_edits,
+            percent(self.valid_edits, self.edits),
+            percent(self.codes_with_valid_edits, self.codes),
+            mean_change,
+            self.max_score_change.format(&settings.score_format)
+        );
+    }
+}
+
+/// The report of the analysis of each code.
+enum Report {
+    Csv(Box<csv::Writer<File>>),
+    Json(BufWriter<File>),
+}
+
+impl Report {
+    /// Writes the analysis of one code: a csv row, or a JSON object per line.
+    fn write(&mut self, analysis: &CodeAnalysis) -> Result<(), String> {
+        match self {
+            Report::Csv(writer) => writer.serialize(analysis).map_err(|err| err.to_string()),
+            Report::Json(writer) => serde_json::to_writer(&mut *writer, analysis)
+                .map_err(|err| err.to_string())
+                .and_then(|_| writeln!(writer).map_err(|err| err.to_string())),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Report::Csv(writer) => writer.flush(),
+            Report::Json(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Runs the subcommand, returning the exit code.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let json = matches
+        .value_of("format")
+        .is_some_and(|f| f.eq_ignore_ascii_case("json"));
+    let mut report = match matches.value_of("report") {
+        Some(path) => match File::create(path) {
+            Ok(file) if json => Some(Report::Json(BufWriter::new(file))),
+            Ok(file) => Some(Report::Csv(Box::new(csv::Writer::from_writer(file)))),
+            Err(err) => {
+                settings.error(&format!("Cannot write the report: {}: {}.", path, err));
+                return 1;
+            }
+        },
+        None => None,
+    };
+
+    settings.info("Analyzing every valid code, this may take a while...");
+    let mut total = Summary::default();
+    let mut by_layout: [Summary; 3] = Default::default();
+    let codes = StyleCode::all_valid()
+        .filter(|style| settings.profile.validate(style).is_ok())
+        .map(|style| {
+            let code = style.encode();
+            if matches.is_present("check") {
+                with_check(&code).unwrap()
+            } else {
+                code
+            }
+        })
+        .collect::<Vec<_>>();
+    // The codes are analyzed in parallel, in batches, and reported in order.
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    for batch in codes.chunks(BATCH_SIZE) {
+        let analyses = thread::scope(|scope| {
+            let handles = batch
+                .chunks(batch.len().div_ceil(threads))
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|code| analyze_code(code, &settings.profile))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        for analysis in analyses {
+            total.add(&analysis);
+            by_layout[analysis.layout.letters() - 1].add(&analysis);
+            if let Some(report) = report.as_mut() {
+                if let Err(err) = report.write(&analysis) {
+                    settings.error(&format!("Cannot write the report: {}.", err));
+                    return 1;
+                }
+            }
+        }
+    }
+    if let Some(Err(err)) = report.as_mut().map(Report::flush) {
+        settings.error(&format!("Cannot write the report: {}.", err));
+        return 1;
+    }
+
+    println!(
+        "{:<8} {:>8} {:>9} {:>12} {:>8} {:>13} {:>11} {:>10}",
+        "Layout",
+        "Codes",
+        "Edits",
+        "Valid edits",
+        "% edits",
+        "% codes hit",
+        "Mean change",
+        "Max change"
+    );
+    for (layout, summary) in Layout::ALL.iter().zip(by_layout.iter()) {
+        summary.print_row(&format!("{}-letter", layout), settings);
+    }
+    total.print_row("All", settings);
+    0
+}
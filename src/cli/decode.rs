@@ -0,0 +1,206 @@
+//! The `decode` subcommand.
+
+use std::io::{self, Write};
+
+use clap::{App, Arg, ArgMatches, SubCommand, Values};
+
+use servizio::{explain_decode, Category, OutputFormat, Record, RecordWriter};
+
+use crate::cli::{format_arg, input_codes, output_format, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("decode")
+        .about("Decodes Style Codes and prints the style judgements")
+        .arg(
+            Arg::with_name("code")
+                .help("The Style Codes to decode. Use - to read codes from standard input, one per line.")
+                .required(true)
+                .multiple(true),
+        )
+        .arg(value_arg().conflicts_with("format"))
+        .arg(
+            Arg::with_name("explain")
+                .help("Explain step by step how each code is decoded")
+                .long("explain")
+                .conflicts_with("format"),
+        )
+        .arg(format_arg())
+}
+
+/// The `--value` option, shared with the legacy decode mode.
+pub fn value_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("value")
+        .help("The requested value: score, bas, mov, din, com, sapd, gcc, dif, sog or pen")
+        .long("value")
+        .short("v")
+        .takes_value(true)
+}
+
+/// Runs the subcommand, returning the exit code.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let format = output_format(matches);
+    // Machine-readable formats only print the records on standard output.
+    let settings = &Settings {
+        raw: settings.raw || format != OutputFormat::Text,
+        ..settings.clone()
+    };
+
+    let requested_value = matches.value_of("value").map(|v| v.to_ascii_lowercase());
+    if let Some(requested_value) = &requested_value {
+        if requested_value != "score" && Category::from_name(requested_value).is_none() {
+            settings.info(&format!("Invalid requested value: {}", requested_value));
+            return 3;
+        }
+    }
+
+    // With several codes, errors name the code they refer to.
+    let args = matches.values_of("code").unwrap();
+    let options = Options {
+        requested_value: requested_value.as_deref(),
+        batch: is_batch(&args),
+        explain: matches.is_present("explain"),
+    };
+
+    let codes = input_codes(args);
+
+    let mut writer = RecordWriter::new(io::stdout(), format, settings.score_format.clone());
+    let summary = decode_all(codes, &options, &mut writer, settings);
+    if writer.finish().is_err() {
+        return 1;
+    }
+
+    match summary.failure_message() {
+        Some(msg) if options.batch => {
+            settings.error(&msg);
+            1
+        }
+        _ => summary.exit_code,
+    }
+}
+
+/// Whether the arguments are decoded as a batch: several codes, or codes
+/// read from standard input, however many lines it has.
+pub fn is_batch(args: &Values) -> bool {
+    args.len() > 1 || args.clone().any(|arg| arg == "-")
+}
+
+/// The outcome of decoding several codes.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Summary {
+    /// The number of codes.
+    pub total: usize,
+    /// The number of codes that could not be decoded.
+    pub failures: usize,
+    /// The exit code of the last failure, or 0.
+    pub exit_code: i32,
+}
+
+impl Summary {
+    /// The message reporting how many codes could not be decoded, if any.
+    pub fn failure_message(&self) -> Option<String> {
+        if self.failures > 0 {
+            Some(format!(
+                "{} of {} codes could not be decoded.",
+                self.failures, self.total
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Decodes and prints every code, going on after the invalid ones.
+pub fn decode_all<I: Iterator<Item = String>, W: Write>(
+    codes: I,
+    options: &Options,
+    writer: &mut RecordWriter<W>,
+    settings: &Settings,
+) -> Summary {
+    let mut summary = Summary {
+        total: 0,
+        failures: 0,
+        exit_code: 0,
+    };
+    for code in codes {
+        summary.total += 1;
+        if let Err(err_code) = decode_one(&code, options, writer, settings) {
+            summary.failures += 1;
+            summary.exit_code = err_code;
+        }
+    }
+    summary
+}
+
+/// How each code is printed.
+pub struct Options<'a> {
+    /// The value requested with `--value`, if any.
+    pub requested_value: Option<&'a str>,
+    /// Whether several codes are decoded, from several arguments or from
+    /// standard input.
+    pub batch: bool,
+    /// Whether to explain how the code is decoded.
+    pub explain: bool,
+}
+
+/// Decodes and prints one code. On failure, returns the exit code.
+fn decode_one<W: Write>(
+    code: &str,
+    options: &Options,
+    writer: &mut RecordWriter<W>,
+    settings: &Settings,
+) -> Result<(), i32> {
+    settings.info(&format!("Decoding input code: {}", code));
+
+    let (style, layout) = match settings.profile.decode_with_layout(code) {
+        Ok(decoded) => decoded,
+        Err(err) => {
+            let msg = if options.batch {
+                format!("Invalid style code: {}: {}.", code, err)
+            } else {
+                format!("Invalid style code: {}.", err)
+            };
+            settings.invalid_code(&msg, code);
+            return Err(1);
+        }
+    };
+    let score = match style.score_with(&settings.profile) {
+        Ok(score) => score,
+        Err(err) => return Err(settings.invalid_score(&err)),
+    };
+
+    if options.explain {
+        // The code was decoded above, so it can be explained.
+        print!("{}", explain_decode(code).unwrap());
+    }
+
+    match options.requested_value {
+        Some(requested_value) => {
+            let out = match Category::from_name(requested_value) {
+                Some(category) => style.get(category).to_string(),
+                None => score.format(&settings.score_format),
+            };
+            if settings.raw {
+                println!("{}", out);
+            } else {
+                println!("{} : {}", requested_value, out);
+            }
+        }
+        None if settings.raw && writer.format() == OutputFormat::Text => {
+            // Legacy raw output: one bare value per line.
+            let out = style.raw_print_with(&settings.profile, &settings.score_format);
+            println!("{}", out.unwrap());
+        }
+        None => {
+            let record = Record {
+                code: code.to_ascii_lowercase(),
+                layout,
+                style,
+                score,
+            };
+            if writer.write(&record).is_err() {
+                return Err(1);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! The `diff` subcommand.
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::{compare, Record, Score};
+
+use crate::cli::{input_codes, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("diff")
+        .about("Compares two or more Style Codes category by category")
+        .arg(
+            Arg::with_name("code")
+                .help("The Style Codes to compare. Use - to read codes from standard input, one per line.")
+                .required(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("The output format. Default is text")
+                .long("format")
+                .short("f")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .case_insensitive(true),
+        )
+}
+
+/// Runs the subcommand, returning the exit code.
+///
+/// With two codes, the table shows the change from the first to the second;
+/// with more, the spread between the smallest and the largest value. In raw
+/// mode, only the categories that disagree are printed.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let json = matches
+        .value_of("format")
+        .is_some_and(|f| f.eq_ignore_ascii_case("json"));
+    // JSON only prints the comparison on standard output.
+    let settings = &Settings {
+        raw: settings.raw || json,
+        ..settings.clone()
+    };
+
+    let mut records = Vec::new();
+    for code in input_codes(matches.values_of("code").unwrap()) {
+        let (style, layout) = match settings.profile.decode_with_layout(&code) {
+            Ok(decoded) => decoded,
+            Err(err) => {
+                settings.invalid_code(&format!("Invalid style code: {}: {}.", code, err), &code);
+                return 1;
+            }
+        };
+        let score = match style.score_with(&settings.profile) {
+            Ok(score) => score,
+            Err(err) => return settings.invalid_score(&err),
+        };
+        records.push(Record {
+            code: code.to_ascii_lowercase(),
+            layout,
+            style,
+            score,
+        });
+    }
+    let comparison = match compare(&records) {
+        Ok(comparison) => comparison,
+        Err(err) => {
+            settings.error(&format!("Cannot compare the codes: {}.", err));
+            return 1;
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string(&comparison).unwrap());
+        return 0;
+    }
+    if settings.raw {
+        println!(
+            "{}",
+            comparison
+                .disagreements
+                .iter()
+                .map(|c| c.name())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        return 0;
+    }
+
+    let score_format = &settings.score_format;
+    let row = |name: &str, values: Vec<String>, change: String| {
+        let values = values
+            .iter()
+            .map(|value| format!(" {:>10}", value))
+            .collect::<String>();
+        let line = format!("{:<6}{} {:>8}", name, values, change);
+        println!("{}", line.trim_end());
+    };
+    row(
+        "",
+        comparison.codes.clone(),
+        match comparison.score_delta {
+            Some(_) => "Delta",
+            None => "Spread",
+        }
+        .to_string(),
+    );
+    for diff in &comparison.categories {
+        let change = match diff.delta {
+            _ if diff.agrees() => String::new(),
+            Some(delta) => format!("{:+}", delta),
+            None => diff.spread.to_string(),
+        };
+        row(
+            &diff.category.to_string(),
+            diff.values.iter().map(|v| v.to_string()).collect(),
+            change,
+        );
+    }
+    row(
+        "Score",
+        comparison
+            .scores
+            .iter()
+            .map(|s| s.format(score_format))
+            .collect(),
+        match comparison.score_delta {
+            Some(delta) if delta > Score::ZERO => format!("+{}", delta.format(score_format)),
+            Some(delta) => delta.format(score_format),
+            None => comparison.score_spread.format(score_format),
+        },
+    );
+    if comparison.disagreements.is_empty() {
+        println!("The codes agree on every category.");
+    } else {
+        println!(
+            "Disagreements: {}",
+            comparison
+                .disagreements
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    0
+}
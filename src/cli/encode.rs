@@ -0,0 +1,110 @@
+//! The `encode` subcommand.
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::{
+    explain_encode_with_layout, parse_judgement, with_check, Layout, OutputFormat, Record,
+    RecordWriter, StyleCode, CHECK_SEPARATOR,
+};
+
+use crate::cli::{format_arg, output_format, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("encode")
+        .about("Encodes a style judgement into a Style Code")
+        .arg(
+            Arg::with_name("judgement")
+                .help("The style judgement to encode, e.g. bas=1,mov=3,gcc=2,pen=1")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("explain")
+                .help("Explain step by step how the judgement is encoded")
+                .long("explain")
+                .conflicts_with("format"),
+        )
+        .arg(
+            Arg::with_name("layout")
+                .help("Write the code with this number of letters, even if a shorter code is possible")
+                .long("layout")
+                .takes_value(true)
+                .possible_values(&["1", "2", "3"]),
+        )
+        .arg(
+            Arg::with_name("check")
+                .help("Append a check character to the code, to detect transcription errors when decoding")
+                .long("check"),
+        )
+        .arg(format_arg())
+}
+
+/// Runs the subcommand, returning the exit code.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let format = output_format(matches);
+    // Machine-readable formats only print the records on standard output.
+    let settings = &Settings {
+        raw: settings.raw || format != OutputFormat::Text,
+        ..settings.clone()
+    };
+
+    // The shortest layout, unless another one was requested.
+    let requested_layout = matches
+        .value_of("layout")
+        .map(|letters| Layout::from_letters(letters.parse().unwrap()).unwrap());
+    let layout_of = |style: &StyleCode| requested_layout.unwrap_or_else(|| style.layout());
+    let checked = matches.is_present("check");
+    let judgement = matches.value_of("judgement").unwrap();
+    settings.info(&format!("Encode: {}", judgement));
+
+    let encoded = parse_judgement(judgement)
+        .map_err(|err| err.to_string())
+        .and_then(|style| {
+            match settings
+                .profile
+                .encode_with_layout(&style, layout_of(&style))
+            {
+                // Codes only hold letters and digits, so they can be checked.
+                Ok(code) if checked => Ok((style, with_check(&code).unwrap())),
+                Ok(code) => Ok((style, code)),
+                Err(err) => Err(err.to_string()),
+            }
+        });
+    match encoded {
+        Ok((style, code)) if format == OutputFormat::Text => {
+            if matches.is_present("explain") {
+                // The judgement was encoded above, so it can be explained.
+                let explanation = explain_encode_with_layout(&style, layout_of(&style));
+                print!("{}", explanation.unwrap());
+                if checked {
+                    println!(
+                        "Check character appended after '{}': {}.",
+                        CHECK_SEPARATOR, code
+                    );
+                }
+            }
+            println!("{}", code);
+            0
+        }
+        Ok((style, code)) => {
+            let record = match style.score_with(&settings.profile) {
+                Ok(score) => Record {
+                    code,
+                    layout: layout_of(&style),
+                    style,
+                    score,
+                },
+                Err(err) => return settings.invalid_score(&err),
+            };
+            let mut writer =
+                RecordWriter::new(std::io::stdout(), format, settings.score_format.clone());
+            if writer.write(&record).and_then(|_| writer.finish()).is_err() {
+                return 1;
+            }
+            0
+        }
+        Err(err) => {
+            settings.error(&format!("Invalid judgement: {}.", err));
+            2
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! The `form` subcommand: a full-screen form to encode a judgement.
+
+use std::io::{self, Write};
+
+use clap::{App, ArgMatches, SubCommand};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use servizio::{Category, StyleCode};
+
+use crate::cli::{judgement_string, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("form").about(
+        "Opens a full-screen form to encode a style judgement, with a live preview of the code",
+    )
+}
+
+/// Restores the terminal when dropped, even if drawing fails.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// The state of the form.
+pub struct Form<'a> {
+    settings: &'a Settings,
+    pub style: StyleCode,
+    pub selected: usize,
+}
+
+impl<'a> Form<'a> {
+    pub fn new(settings: &'a Settings) -> Form<'a> {
+        Form {
+            settings,
+            style: StyleCode::default(),
+            selected: 0,
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        Category::ALL[self.selected]
+    }
+
+    /// Sets the selected category, keeping it within the limits of the profile.
+    pub fn set(&mut self, value: u32) {
+        let category = self.category();
+        *self.style.get_mut(category) = value.min(self.settings.profile.limit(category));
+    }
+
+    /// Handles a key press. Returns `Some(true)` to accept the judgement,
+    /// `Some(false)` to cancel and `None` to keep editing.
+    pub fn handle(&mut self, key: KeyEvent) -> Option<bool> {
+        let value = self.style.get(self.category());
+        match key.code {
+            KeyCode::Enter => return Some(true),
+            KeyCode::Esc | KeyCode::Char('q') => return Some(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(false)
+            }
+            KeyCode::Up | KeyCode::BackTab | KeyCode::Char('k') => {
+                self.selected = (self.selected + Category::ALL.len() - 1) % Category::ALL.len()
+            }
+            KeyCode::Down | KeyCode::Tab | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1) % Category::ALL.len()
+            }
+            KeyCode::Left | KeyCode::Char('-') | KeyCode::Char('h') => {
+                self.set(value.saturating_sub(1))
+            }
+            KeyCode::Right | KeyCode::Char('+') | KeyCode::Char('l') => self.set(value + 1),
+            KeyCode::Char(digit @ '0'..='9') => self.set(digit.to_digit(10).unwrap()),
+            KeyCode::Delete | KeyCode::Backspace => self.set(0),
+            _ => {}
+        }
+        None
+    }
+
+    fn draw<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let settings = self.settings;
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(0, 0),
+            Print("Encode a style judgement"),
+            MoveTo(0, 1),
+            Print(format!("Profile: {}", settings.profile.name())),
+        )?;
+
+        for (i, &category) in Category::ALL.iter().enumerate() {
+            let line = format!(
+                "{:<5} < {:>2} >   (0-{})",
+                category.to_string(),
+                self.style.get(category),
+                settings.profile.limit(category)
+            );
+            queue!(out, MoveTo(2, 3 + i as u16))?;
+            if i == self.selected {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(line),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, Print(line))?;
+            }
+        }
+
+        // The encoding cannot fail: every value is within the profile limits,
+        // which never exceed what a code can represent.
+        let code = self.style.try_encode().unwrap_or_default();
+        let score = match self.style.score_with(&settings.profile) {
+            Ok(score) => score.format(&settings.score_format),
+            Err(err) => format!("invalid ({})", err),
+        };
+        queue!(
+            out,
+            MoveTo(36, 3),
+            Print(format!("Code   : {}", code)),
+            MoveTo(36, 4),
+            Print(format!("Layout : {}-letter", self.style.layout())),
+            MoveTo(36, 5),
+            Print(format!("Score  : {}", score)),
+            MoveTo(0, 13),
+            Print(
+                "Up/Down: select   Left/Right or 0-9: change value   Enter: accept   Esc: cancel"
+            ),
+        )?;
+        out.flush()
+    }
+
+    fn run(&mut self) -> io::Result<bool> {
+        let _screen = Screen::enter()?;
+        let mut out = io::stdout();
+        loop {
+            self.draw(&mut out)?;
+            if let Event::Key(key) = event::read()? {
+                if let Some(accepted) = self.handle(key) {
+                    return Ok(accepted);
+                }
+            }
+        }
+    }
+}
+
+/// Runs the subcommand, returning the exit code.
+///
+/// When the judgement is accepted, its code is printed to standard output.
+pub fn run(_matches: &ArgMatches, settings: &Settings) -> i32 {
+    let mut form = Form::new(settings);
+    match form.run() {
+        Ok(true) => {
+            settings.info(&format!("Encode: {}", judgement_string(&form.style)));
+            println!("{}", form.style.try_encode().unwrap());
+            0
+        }
+        Ok(false) => 1,
+        Err(err) => {
+            settings.error(&format!("Cannot open the form: {}.", err));
+            1
+        }
+    }
+}
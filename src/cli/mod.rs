@@ -0,0 +1,222 @@
+//! Subcommands of the command-line utility.
+
+use std::io::{self, BufRead};
+
+use clap::{Arg, ArgMatches, Values};
+
+use servizio::{
+    suggest, Category, OutputFormat, ProcessError, Score, ScoreError, ScoreFormat, ScorePolicy,
+    ScoringProfile, StyleCode,
+};
+
+pub mod adjust;
+pub mod agreement;
+pub mod analyze;
+pub mod decode;
+pub mod diff;
+pub mod encode;
+pub mod form;
+pub mod normalize;
+pub mod panel;
+pub mod process;
+pub mod repl;
+pub mod search;
+pub mod table;
+pub mod target;
+
+#[cfg(test)]
+mod tests;
+
+/// Options shared by all the subcommands.
+#[derive(Clone)]
+pub struct Settings {
+    pub raw: bool,
+    pub profile: ScoringProfile,
+    pub score_format: ScoreFormat,
+}
+
+impl Settings {
+    /// Reads the global options. On failure, returns an error message and the
+    /// exit code the program should use.
+    pub fn from_matches(matches: &ArgMatches) -> Result<Settings, (String, i32)> {
+        let raw = matches.is_present("raw");
+
+        let profile = match matches.value_of("profile") {
+            Some(name) => ScoringProfile::select(name)
+                .map_err(|err| (format!("Invalid profile: {}.", err), 6))?,
+            None => ScoringProfile::default(),
+        };
+
+        // The score policy of the profile can be overridden from the command line.
+        let (profile_policy, profile_floor) = match profile.score_policy() {
+            ScorePolicy::Clamp(floor) => ("clamp", floor),
+            ScorePolicy::AllowNegative => ("allow", Score::ZERO),
+            ScorePolicy::Reject(floor) => ("reject", floor),
+        };
+        let score_floor = match matches.value_of("score-floor") {
+            Some(floor) => floor
+                .parse::<Score>()
+                .map_err(|_| (format!("Invalid score floor: {}", floor), 5))?,
+            None => profile_floor,
+        };
+        let score_policy = match matches.value_of("score-policy").unwrap_or(profile_policy) {
+            "allow" => ScorePolicy::AllowNegative,
+            "reject" => ScorePolicy::Reject(score_floor),
+            _ => ScorePolicy::Clamp(score_floor),
+        };
+
+        let score_format = ScoreFormat {
+            decimals: match matches.value_of("decimals") {
+                Some(decimals) => decimals
+                    .parse::<usize>()
+                    .map_err(|_| ("Invalid number of decimals".to_string(), 5))?,
+                None => 1,
+            },
+            decimal_comma: matches.is_present("decimal-comma"),
+        };
+
+        Ok(Settings {
+            raw,
+            profile: profile.with_score_policy(score_policy),
+            score_format,
+        })
+    }
+
+    /// Prints a message about the input, unless in raw mode.
+    pub fn info(&self, msg: &str) {
+        if !self.raw {
+            println!("{}", msg);
+        }
+    }
+
+    /// Prints an error message, to standard error in raw mode so that the
+    /// output stays parseable.
+    pub fn error(&self, msg: &str) {
+        if self.raw {
+            eprintln!("{}", msg);
+        } else {
+            println!("{}", msg);
+        }
+    }
+
+    /// Prints an error for an invalid code, followed by the valid codes it may
+    /// have been meant to be.
+    pub fn invalid_code(&self, msg: &str, code: &str) {
+        self.error(&invalid_code_message(msg, code));
+    }
+
+    /// Prints an error for a rejected score and returns the exit code.
+    pub fn invalid_score(&self, err: &ScoreError) -> i32 {
+        self.error(&format!("Invalid score: {}.", err));
+        5
+    }
+}
+
+/// An error for an invalid code, followed on the next lines by the valid
+/// codes it may have been meant to be.
+pub fn invalid_code_message(msg: &str, code: &str) -> String {
+    let mut lines = vec![msg.to_string()];
+    let suggestions = suggest(code, 5);
+    if !suggestions.is_empty() {
+        lines.push("Did you mean:".to_string());
+        for suggestion in suggestions {
+            lines.push(format!("  {:<10} ({})", suggestion.code, suggestion.typo));
+        }
+    }
+    lines.join("\n")
+}
+
+/// A mode selected with the legacy flags, which were replaced by the
+/// subcommands but are still accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyMode {
+    /// `servizio-cli <code>`, now `decode`.
+    Decode,
+    /// `servizio-cli --encode=<judgement>`, now `encode`.
+    Encode,
+    /// `servizio-cli --infile=<infile> --outfile=<outfile>`, now `process`.
+    Process,
+}
+
+impl LegacyMode {
+    /// The legacy mode selected by the top-level arguments, if any.
+    pub fn from_matches(matches: &ArgMatches) -> Option<LegacyMode> {
+        if matches.is_present("code") {
+            Some(LegacyMode::Decode)
+        } else if matches.is_present("judgement") {
+            Some(LegacyMode::Encode)
+        } else if matches.is_present("infile") {
+            Some(LegacyMode::Process)
+        } else {
+            None
+        }
+    }
+
+    /// The warning printed when the mode is used.
+    pub fn warning(self) -> &'static str {
+        match self {
+            LegacyMode::Decode => "Warning: `servizio-cli <code>` is deprecated, use `servizio-cli decode <code>` instead.",
+            LegacyMode::Encode => "Warning: `servizio-cli --encode=<judgement>` is deprecated, use `servizio-cli encode <judgement>` instead.",
+            LegacyMode::Process => "Warning: `servizio-cli --infile=<infile> --outfile=<outfile>` is deprecated, use `servizio-cli process <infile> <outfile>` instead.",
+        }
+    }
+}
+
+/// The exit code for an error reading or writing a `csv` file.
+pub fn file_error_code(err: &ProcessError) -> i32 {
+    match err {
+        ProcessError::SameFile(_) => 1001,
+        ProcessError::OpenInput { .. } => 1002,
+        ProcessError::CreateOutput { .. } => 1003,
+        ProcessError::Read { .. } => 1004,
+        ProcessError::Write { .. } => 1005,
+        _ => 1,
+    }
+}
+
+/// The `--format` option of the subcommands that print judgements.
+pub fn format_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("format")
+        .help("The output format. Default is text")
+        .long("format")
+        .short("f")
+        .takes_value(true)
+        .possible_values(&OutputFormat::NAMES)
+        .case_insensitive(true)
+}
+
+/// Reads the `--format` option.
+pub fn output_format(matches: &ArgMatches) -> OutputFormat {
+    matches
+        .value_of("format")
+        .map_or(OutputFormat::Text, |f| f.parse().unwrap())
+}
+
+/// The codes given as arguments. In place of `-`, codes are read from
+/// standard input, one per line, skipping blank lines.
+pub fn input_codes<'a>(args: Values<'a>) -> impl Iterator<Item = String> + 'a {
+    args.flat_map(|arg| {
+        if arg == "-" {
+            let lines = io::stdin()
+                .lock()
+                .lines()
+                .map_while(Result::ok)
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty());
+            Box::new(lines) as Box<dyn Iterator<Item = String>>
+        } else {
+            Box::new(std::iter::once(arg.to_string()))
+        }
+    })
+}
+
+/// The judgement in the format accepted by the `encode` subcommand, listing
+/// only the categories that are not zero.
+pub fn judgement_string(style: &StyleCode) -> String {
+    Category::ALL
+        .iter()
+        .filter(|&&category| style.get(category) > 0)
+        .map(|&category| format!("{}={}", category.name(), style.get(category)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -0,0 +1,71 @@
+//! The `normalize` subcommand.
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::StyleCode;
+
+use crate::cli::{input_codes, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("normalize")
+        .about("Checks whether Style Codes are canonical and prints their canonical form")
+        .arg(
+            Arg::with_name("code")
+                .help("The Style Codes to normalize. Use - to read codes from standard input, one per line.")
+                .required(true)
+                .multiple(true),
+        )
+}
+
+/// Runs the subcommand, returning the exit code.
+///
+/// In raw mode, only the canonical form of each code is printed.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let mut total = 0;
+    let mut failures = 0;
+    let mut not_canonical = 0;
+    for code in input_codes(matches.values_of("code").unwrap()) {
+        total += 1;
+        // The code must also be within the limits of the profile.
+        let normalized = settings
+            .profile
+            .decode(&code)
+            .and_then(|_| Ok(StyleCode::normalize(&code)?));
+        match normalized {
+            Ok(canonical) if canonical == code => {
+                settings.info(&format!("{}: canonical", code));
+                if settings.raw {
+                    println!("{}", canonical);
+                }
+            }
+            Ok(canonical) => {
+                not_canonical += 1;
+                settings.info(&format!(
+                    "{}: not canonical, canonical form: {}",
+                    code, canonical
+                ));
+                if settings.raw {
+                    println!("{}", canonical);
+                }
+            }
+            Err(err) => {
+                failures += 1;
+                settings.error(&format!("Invalid style code: {}: {}.", code, err));
+            }
+        }
+    }
+
+    settings.info(&format!(
+        "{} of {} codes are not canonical.",
+        not_canonical,
+        total - failures
+    ));
+    if failures > 0 {
+        settings.error(&format!(
+            "{} of {} codes could not be decoded.",
+            failures, total
+        ));
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,115 @@
+//! The `panel` subcommand.
+
+use std::io;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::{aggregate, AggregationMethod, OutputFormat, Record, RecordWriter};
+
+use crate::cli::{format_arg, input_codes, judgement_string, output_format, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("panel")
+        .about("Aggregates the Style Codes of a panel of judges into a final judgement")
+        .arg(
+            Arg::with_name("code")
+                .help("The Style Codes of the judges. Use - to read codes from standard input, one per line.")
+                .required(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("method")
+                .help("How the values of each category are combined: mean, median or trimmed (the mean without the highest and lowest value). Default is mean")
+                .long("method")
+                .short("m")
+                .takes_value(true)
+                .possible_values(&AggregationMethod::NAMES)
+                .case_insensitive(true),
+        )
+        .arg(format_arg())
+}
+
+/// Runs the subcommand, returning the exit code.
+///
+/// In raw mode, only the final code is printed.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let format = output_format(matches);
+    // Machine-readable formats only print the records on standard output.
+    let settings = &Settings {
+        raw: settings.raw || format != OutputFormat::Text,
+        ..settings.clone()
+    };
+    let method = matches
+        .value_of("method")
+        .map_or(AggregationMethod::Mean, |m| m.parse().unwrap());
+
+    let mut judgements = Vec::new();
+    for code in input_codes(matches.values_of("code").unwrap()) {
+        let decoded = settings.profile.decode(&code);
+        match decoded {
+            Ok(style) => judgements.push((code.to_ascii_lowercase(), style)),
+            Err(err) => {
+                settings.invalid_code(&format!("Invalid style code: {}: {}.", code, err), &code);
+                return 1;
+            }
+        }
+    }
+
+    let styles = judgements
+        .iter()
+        .map(|(_, style)| *style)
+        .collect::<Vec<_>>();
+    let result = match aggregate(&styles, method) {
+        Ok(result) => result,
+        Err(err) => {
+            settings.error(&format!("Cannot aggregate the judgements: {}.", err));
+            return 1;
+        }
+    };
+    let score = match result.score_with(&settings.profile) {
+        Ok(score) => score,
+        Err(err) => return settings.invalid_score(&err),
+    };
+    let record = Record {
+        code: result.encode(),
+        layout: result.layout(),
+        style: result,
+        score,
+    };
+
+    if format != OutputFormat::Text {
+        let mut writer = RecordWriter::new(io::stdout(), format, settings.score_format.clone());
+        if writer.write(&record).and_then(|_| writer.finish()).is_err() {
+            return 1;
+        }
+        return 0;
+    }
+    if settings.raw {
+        println!("{}", record.code);
+        return 0;
+    }
+
+    let score_format = &settings.score_format;
+    for (i, (code, style)) in judgements.iter().enumerate() {
+        let score = match style.score_with(&settings.profile) {
+            Ok(score) => score.format(score_format),
+            Err(err) => format!("invalid ({})", err),
+        };
+        println!(
+            "{:<9} {:<10} {:>6}  {}",
+            format!("Judge {}", i + 1),
+            code,
+            score,
+            judgement_string(style)
+        );
+    }
+    println!("Method: {}", method);
+    println!(
+        "{:<9} {:<10} {:>6}  {}",
+        "Final",
+        record.code,
+        score.format(score_format),
+        judgement_string(&record.style)
+    );
+    0
+}
@@ -0,0 +1,82 @@
+//! The `process` subcommand.
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::{process_file, CsvProcessor};
+
+use crate::cli::{file_error_code, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("process")
+        .about("Decodes a column of Style Codes in a csv file, appending the decoded values to each row")
+        .arg(
+            Arg::with_name("infile")
+                .help("The input file to process")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("outfile")
+                .help("The output file to write")
+                .required(true),
+        )
+        .args(&options())
+}
+
+/// The options shared with the legacy file processing mode.
+pub fn options<'a>() -> Vec<Arg<'a, 'a>> {
+    vec![
+        Arg::with_name("headers")
+            .help("Treat the first line as column headers, and append the names of the decoded columns to it")
+            .long("headers"),
+        Arg::with_name("column")
+            .help("Specifies the index of the column to decode (zero-based). Default is the last column, as determined by the first row.")
+            .long("column")
+            .min_values(0)
+            .takes_value(true),
+        Arg::with_name("normalize")
+            .help("Rewrite the valid codes in their canonical form (the code that encode would produce)")
+            .long("normalize"),
+        Arg::with_name("suggest")
+            .help("Append a Suggestions column with the valid codes that each invalid code may have been meant to be")
+            .long("suggest"),
+        Arg::with_name("delimiter")
+            .help("Specifies the delimiter in the CSV files. Default is comma")
+            .long("delimiter")
+            .takes_value(true),
+    ]
+}
+
+/// Runs the subcommand, returning the exit code.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let infile = matches.value_of("infile").unwrap();
+    let outfile = matches.value_of("outfile").unwrap();
+    let has_headers = matches.is_present("headers");
+    let column_index = matches
+        .value_of("column")
+        .and_then(|s| s.parse::<usize>().ok());
+
+    let delimiter = match matches.value_of("delimiter") {
+        Some(d) => {
+            let bytes = &d.as_bytes();
+            if bytes.len() != 1 {
+                settings.info("Invalid delimiter");
+                return 4;
+            }
+            bytes[0] as char
+        }
+        None => ',',
+    };
+
+    let processor = CsvProcessor::new(has_headers, column_index)
+        .with_profile(settings.profile.clone())
+        .with_score_format(settings.score_format.clone())
+        .with_normalize(matches.is_present("normalize"))
+        .with_suggestions(matches.is_present("suggest"));
+    if let Err(err) = process_file(infile, outfile, delimiter, processor) {
+        println!("An error occurred: {}.", err);
+        file_error_code(&err)
+    } else {
+        println!("Processing completed.");
+        0
+    }
+}
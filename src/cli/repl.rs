@@ -0,0 +1,225 @@
+//! The `repl` subcommand: an interactive session to decode and encode codes.
+
+use std::fs::File;
+use std::io::{self, Write};
+
+use clap::{App, ArgMatches, SubCommand};
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+
+use servizio::{parse_judgement, OutputFormat, Record, RecordWriter};
+
+use crate::cli::{format_arg, invalid_code_message, output_format, Settings};
+
+static HELP: &str = "\
+Type a Style Code (e.g. g13dm12) to decode it, or a judgement (e.g. bas=1,mov=3) to encode it.
+Commands:
+  :format <format>  Switch the output format (text, json, csv, tsv, yaml)
+  :log              Show the codes decoded or encoded in this session
+  :save <file>      Save the session log as a csv file
+  :clear            Clear the session log
+  :help             Show this message
+  :quit             End the session (also Ctrl-D)";
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("repl")
+        .about("Starts an interactive session to decode and encode Style Codes")
+        .arg(format_arg())
+}
+
+/// The state of an interactive session.
+pub struct Session<'a, W: Write> {
+    settings: &'a Settings,
+    /// Where the records and the messages are printed.
+    out: W,
+    /// Formats the records, which are then moved to `out`.
+    writer: RecordWriter<Vec<u8>>,
+    log: Vec<Record>,
+}
+
+impl<'a, W: Write> Session<'a, W> {
+    pub fn new(settings: &'a Settings, format: OutputFormat, out: W) -> Session<'a, W> {
+        Session {
+            settings,
+            out,
+            writer: RecordWriter::new(Vec::new(), format, settings.score_format.clone()),
+            log: Vec::new(),
+        }
+    }
+
+    /// The records decoded or encoded in the session, and not cleared.
+    #[cfg(test)]
+    pub fn log(&self) -> &[Record] {
+        &self.log
+    }
+
+    /// The output of the session.
+    #[cfg(test)]
+    pub fn into_output(self) -> W {
+        self.out
+    }
+
+    /// Prints a message. The session goes on if the output cannot be written,
+    /// as there is nowhere else to report it.
+    fn print(&mut self, msg: &str) {
+        writeln!(self.out, "{}", msg).ok();
+    }
+
+    /// Handles one line of input. Returns `false` when the session should end.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None => {}
+            Some(":quit") | Some(":q") | Some(":exit") => return false,
+            Some(":help") | Some(":h") => self.print(HELP),
+            Some(":format") => match words.next().map(str::parse::<OutputFormat>) {
+                Some(Ok(format)) => {
+                    self.writer =
+                        RecordWriter::new(Vec::new(), format, self.settings.score_format.clone());
+                    self.print(&format!("Output format: {}", format));
+                }
+                Some(Err(err)) => self.print(&format!("Invalid format: {}.", err)),
+                None => self.print(&format!("Output format: {}", self.writer.format())),
+            },
+            Some(":log") => self.print_log(),
+            Some(":save") => match words.next() {
+                Some(path) => match self.save(path) {
+                    Ok(()) => self.print(&format!("Saved {} entries to {}", self.log.len(), path)),
+                    Err(err) => self.print(&format!("Cannot save the session log: {}.", err)),
+                },
+                None => self.print("Usage: :save <file>"),
+            },
+            Some(":clear") => {
+                self.log.clear();
+                self.print("Session log cleared.");
+            }
+            Some(command) if command.starts_with(':') => self.print(&format!(
+                "Unknown command: {} (type :help for help)",
+                command
+            )),
+            Some(_) if line.contains('=') => self.encode(line),
+            Some(_) => self.decode(line),
+        }
+        true
+    }
+
+    fn decode(&mut self, code: &str) {
+        let settings = self.settings;
+        let (style, layout) = match settings.profile.decode_with_layout(code) {
+            Ok(decoded) => decoded,
+            Err(err) => {
+                let msg = format!("Invalid style code: {}.", err);
+                return self.print(&invalid_code_message(&msg, code));
+            }
+        };
+        let score = match style.score_with(&settings.profile) {
+            Ok(score) => score,
+            Err(err) => return self.print(&format!("Invalid score: {}.", err)),
+        };
+        self.emit(Record {
+            code: code.to_ascii_lowercase(),
+            layout,
+            style,
+            score,
+        });
+    }
+
+    fn encode(&mut self, judgement: &str) {
+        let settings = self.settings;
+        let encoded = parse_judgement(judgement)
+            .map_err(|err| err.to_string())
+            .and_then(|style| match settings.profile.encode(&style) {
+                Ok(code) => Ok((style, code)),
+                Err(err) => Err(err.to_string()),
+            });
+        let (style, code) = match encoded {
+            Ok(encoded) => encoded,
+            Err(err) => return self.print(&format!("Invalid judgement: {}.", err)),
+        };
+        let score = match style.score_with(&settings.profile) {
+            Ok(score) => score,
+            Err(err) => return self.print(&format!("Invalid score: {}.", err)),
+        };
+        if self.writer.format() == OutputFormat::Text {
+            self.print(&format!("Code : {}", code));
+        }
+        self.emit(Record {
+            code,
+            layout: style.layout(),
+            style,
+            score,
+        });
+    }
+
+    /// Prints a record in the current format and adds it to the log.
+    fn emit(&mut self, record: Record) {
+        let written = self.writer.write(&record).and_then(|_| {
+            let formatted = std::mem::take(self.writer.get_mut());
+            self.out.write_all(&formatted)
+        });
+        if let Err(err) = written {
+            self.print(&format!("Cannot write the output: {}.", err));
+        }
+        self.log.push(record);
+    }
+
+    fn print_log(&mut self) {
+        if self.log.is_empty() {
+            return self.print("The session log is empty.");
+        }
+        let lines = self
+            .log
+            .iter()
+            .enumerate()
+            .map(|(i, record)| {
+                format!(
+                    "{:>4}  {:<10} {}",
+                    i + 1,
+                    record.code,
+                    record.score.format(&self.settings.score_format)
+                )
+            })
+            .collect::<Vec<_>>();
+        self.print(&lines.join("\n"));
+    }
+
+    /// Writes the session log to `path` in the csv output format.
+    fn save(&self, path: &str) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut writer =
+            RecordWriter::new(file, OutputFormat::Csv, self.settings.score_format.clone());
+        for record in &self.log {
+            writer.write(record)?;
+        }
+        writer.finish().map(|_| ())
+    }
+}
+
+/// Runs the subcommand, returning the exit code.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let mut editor = Editor::<()>::new();
+    let mut session = Session::new(settings, output_format(matches), io::stdout());
+
+    println!("servizio-cli interactive session. Type :help for help, :quit to exit.");
+    loop {
+        match editor.readline("> ") {
+            Ok(line) => {
+                let line = line.trim();
+                if !line.is_empty() {
+                    editor.add_history_entry(line);
+                }
+                if !session.handle(line) {
+                    break;
+                }
+            }
+            // Ctrl-C discards the current line.
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                settings.error(&format!("Cannot read the input: {}.", err));
+                return 1;
+            }
+        }
+    }
+    0
+}
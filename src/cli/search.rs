@@ -0,0 +1,111 @@
+//! The `search` subcommand.
+
+use std::io;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::{Constraint, Field, OutputFormat, RecordWriter, Search};
+
+use crate::cli::{format_arg, judgement_string, output_format, Settings};
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("search")
+        .about("Lists the judgements that match constraints on their score, categories or code")
+        .arg(
+            Arg::with_name("constraint")
+                .help("The constraints, e.g. score=7.5, pen=0, mov>=2 or code=g1*. Operators: = != < <= > >=")
+                .required(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .help("Sort the judgements on this field: code, layout, score or a category")
+                .long("sort")
+                .takes_value(true)
+                .validator(|field| match Field::from_name(&field) {
+                    Some(_) => Ok(()),
+                    None => Err(format!("unknown field '{}'", field)),
+                }),
+        )
+        .arg(
+            Arg::with_name("descending")
+                .help("Sort in descending order")
+                .long("descending")
+                .requires("sort"),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .help("List at most this number of judgements")
+                .long("limit")
+                .takes_value(true)
+                .validator(|limit| match limit.parse::<usize>() {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(format!("invalid limit '{}'", limit)),
+                }),
+        )
+        .arg(format_arg())
+}
+
+/// Runs the subcommand, returning the exit code.
+///
+/// In the text format, each judgement is printed on one line; in raw mode,
+/// only the codes are printed.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let format = output_format(matches);
+    // Machine-readable formats only print the records on standard output.
+    let settings = &Settings {
+        raw: settings.raw || format != OutputFormat::Text,
+        ..settings.clone()
+    };
+
+    let mut constraints = Vec::new();
+    for constraint in matches.values_of("constraint").unwrap() {
+        match constraint.parse::<Constraint>() {
+            Ok(constraint) => constraints.push(constraint),
+            Err(err) => {
+                settings.error(&format!("Invalid constraint: {}.", err));
+                return 2;
+            }
+        }
+    }
+    let mut search = Search::new(constraints);
+    if let Some(field) = matches.value_of("sort") {
+        let field = Field::from_name(field).unwrap();
+        search = search.with_sort(field, matches.is_present("descending"));
+    }
+    if let Some(limit) = matches.value_of("limit") {
+        search = search.with_limit(limit.parse().unwrap());
+    }
+
+    let records = search.run(&settings.profile);
+    if format != OutputFormat::Text {
+        let mut writer = RecordWriter::new(io::stdout(), format, settings.score_format.clone());
+        for record in &records {
+            if writer.write(record).is_err() {
+                return 1;
+            }
+        }
+        return if writer.finish().is_ok() { 0 } else { 1 };
+    }
+
+    if settings.raw {
+        for record in &records {
+            println!("{}", record.code);
+        }
+        return 0;
+    }
+    for record in &records {
+        println!(
+            "{:<10} {:>6}  {}",
+            record.code,
+            record.score.format(&settings.score_format),
+            judgement_string(&record.style)
+        );
+    }
+    match records.len() {
+        0 => println!("No judgement matches."),
+        1 => println!("1 judgement found."),
+        n => println!("{} judgements found.", n),
+    }
+    0
+}
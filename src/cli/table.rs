@@ -0,0 +1,215 @@
+//! The `table` subcommand.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+use servizio::{
+    table, Category, Layout, OutputFormat, Record, RecordWriter, ScoreFormat, TableFilter,
+};
+
+use crate::cli::Settings;
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("table")
+        .about("Writes the lookup table of every valid code, with its judgement and score")
+        .arg(
+            Arg::with_name("format")
+                .help("The output format. sqlite requires --output. Default is csv")
+                .long("format")
+                .short("f")
+                .takes_value(true)
+                .possible_values(&["text", "json", "csv", "tsv", "yaml", "sqlite"])
+                .case_insensitive(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .help("Write the table to this file instead of standard output")
+                .long("output")
+                .short("o")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("force")
+                .help("Replace the database if the --output file of the sqlite format exists")
+                .long("force"),
+        )
+        .arg(
+            Arg::with_name("filter")
+                .help("List only the judgements with these values, e.g. pen=0 or bas=1-2,mov=2-")
+                .long("filter")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("layout")
+                .help("List only the codes with this number of letters. Can be repeated")
+                .long("layout")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&["1", "2", "3"]),
+        )
+}
+
+/// Writes the records to a new SQLite database, in a `codes` table with the
+/// same columns as the other formats. The score is stored as text, e.g.
+/// `7.2`, and exactly in tenths in an extra `score_tenths` column, so that
+/// sums and averages are not affected by floating-point rounding. Returns the
+/// number of records.
+fn write_sqlite<I: Iterator<Item = Record>>(
+    path: &Path,
+    records: I,
+    score_format: &ScoreFormat,
+) -> rusqlite::Result<usize> {
+    let mut connection = Connection::open(path)?;
+    let categories = Category::ALL.iter().map(|c| c.name()).collect::<Vec<_>>();
+    connection.execute(
+        &format!(
+            "CREATE TABLE codes (code TEXT PRIMARY KEY, layout INTEGER NOT NULL, score TEXT NOT NULL, {} INTEGER NOT NULL, score_tenths INTEGER NOT NULL)",
+            categories.join(" INTEGER NOT NULL, ")
+        ),
+        [],
+    )?;
+
+    let transaction = connection.transaction()?;
+    let mut count = 0;
+    {
+        let mut insert = transaction.prepare(&format!(
+            "INSERT INTO codes (code, layout, score, {}, score_tenths) VALUES (?, ?, ?{}, ?)",
+            categories.join(", "),
+            ", ?".repeat(categories.len())
+        ))?;
+        for record in records {
+            let mut values = vec![
+                Value::Text(record.code.clone()),
+                Value::Integer(record.layout.letters() as i64),
+                Value::Text(record.score.format(score_format)),
+            ];
+            values.extend(
+                Category::ALL
+                    .iter()
+                    .map(|&c| Value::Integer(i64::from(record.style.get(c)))),
+            );
+            values.push(Value::Integer(i64::from(record.score.tenths())));
+            insert.execute(params_from_iter(values))?;
+            count += 1;
+        }
+    }
+    transaction.commit()?;
+    Ok(count)
+}
+
+/// Writes the records in one of the output formats. Returns the number of
+/// records.
+fn write_records<W: Write, I: Iterator<Item = Record>>(
+    writer: &mut RecordWriter<W>,
+    records: I,
+) -> io::Result<usize> {
+    let mut count = 0;
+    for record in records {
+        writer.write(&record)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Runs the subcommand, returning the exit code.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let filter = match matches.value_of("filter").map(TableFilter::parse) {
+        Some(Ok(filter)) => filter,
+        Some(Err(err)) => {
+            settings.error(&format!("Invalid filter: {}.", err));
+            return 2;
+        }
+        None => TableFilter::default(),
+    };
+    let filter = matches
+        .values_of("layout")
+        .into_iter()
+        .flatten()
+        .filter_map(|letters| Layout::from_letters(letters.parse().unwrap()))
+        .fold(filter, TableFilter::with_layout);
+    let records = table(&settings.profile, &filter);
+    let output = matches.value_of("output");
+    let format = matches.value_of("format").unwrap_or("csv");
+
+    let written = if format.eq_ignore_ascii_case("sqlite") {
+        let path = match output {
+            Some(path) => Path::new(path),
+            None => {
+                settings.error("The sqlite format requires --output.");
+                return 1;
+            }
+        };
+        // The table is created from scratch, in place of the file only if
+        // asked to.
+        if path.exists() {
+            if !matches.is_present("force") {
+                settings.error(&format!(
+                    "Cannot write the table: {} already exists (use --force to replace it).",
+                    path.display()
+                ));
+                return 1;
+            }
+            settings.info(&format!("Replacing {}.", path.display()));
+            if let Err(err) = fs::remove_file(path) {
+                settings.error(&format!(
+                    "Cannot write the table: {}: {}.",
+                    path.display(),
+                    err
+                ));
+                return 1;
+            }
+        }
+        write_sqlite(path, records, &settings.score_format).map_err(|err| err.to_string())
+    } else {
+        let format: OutputFormat = format.parse().unwrap();
+        let score_format = settings.score_format.clone();
+        match output {
+            Some(path) => match File::create(path) {
+                Ok(file) => {
+                    let mut writer = RecordWriter::new(BufWriter::new(file), format, score_format);
+                    write_records(&mut writer, records)
+                        .and_then(|count| writer.finish().map(|_| count))
+                        .map_err(|err| err.to_string())
+                }
+                Err(err) => Err(format!("{}: {}", path, err)),
+            },
+            None => {
+                let stdout = io::stdout();
+                let mut writer =
+                    RecordWriter::new(BufWriter::new(stdout.lock()), format, score_format);
+                match write_records(&mut writer, records)
+                    .and_then(|count| writer.finish().map(|_| count))
+                {
+                    // The reader stopped early, e.g. `head`.
+                    Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return 0,
+                    written => written.map_err(|err| err.to_string()),
+                }
+            }
+        }
+    };
+    match written {
+        Ok(count) => {
+            // The table itself may be on standard output.
+            if output.is_some() {
+                settings.info(&format!("Wrote {} codes.", count));
+            }
+            0
+        }
+        Err(err) => {
+            let msg = format!("Cannot write the table: {}.", err);
+            // Keep the message out of a table written to standard output.
+            if output.is_none() {
+                eprintln!("{}", msg);
+            } else {
+                settings.error(&msg);
+            }
+            1
+        }
+    }
+}
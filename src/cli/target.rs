@@ -0,0 +1,112 @@
+//! The `target` subcommand.
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use servizio::{reach_target, Score};
+
+use crate::cli::Settings;
+
+pub fn subcommand<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("target")
+        .about("Lists the smallest changes to a judgement that reach a target score")
+        .arg(
+            Arg::with_name("code")
+                .help("The Style Code of the current judgement")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("score")
+                .help("The target score")
+                .required(true)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .help("List at most this number of changes. Default is 10")
+                .long("limit")
+                .takes_value(true)
+                .validator(|limit| match limit.parse::<usize>() {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(format!("invalid limit '{}'", limit)),
+                }),
+        )
+}
+
+/// Runs the subcommand, returning the exit code.
+///
+/// In raw mode, only the codes of the new judgements are printed.
+pub fn run(matches: &ArgMatches, settings: &Settings) -> i32 {
+    let code = matches.value_of("code").unwrap();
+    let style = match settings.profile.decode(code) {
+        Ok(style) => style,
+        Err(err) => {
+            settings.invalid_code(&format!("Invalid style code: {}.", err), code);
+            return 1;
+        }
+    };
+    let score = match style.score_with(&settings.profile) {
+        Ok(score) => score,
+        Err(err) => return settings.invalid_score(&err),
+    };
+    let target = match matches.value_of("score").unwrap().parse::<Score>() {
+        Ok(target) => target,
+        Err(err) => {
+            settings.error(&format!("Invalid target score: {}.", err));
+            return 5;
+        }
+    };
+    let limit = matches
+        .value_of("limit")
+        .map_or(10, |limit| limit.parse().unwrap());
+
+    let format = &settings.score_format;
+    let plans = reach_target(&style, target, &settings.profile);
+    settings.info(&format!(
+        "From {} ({}) to {}:",
+        code.to_ascii_lowercase(),
+        score.format(format),
+        target.format(format)
+    ));
+    if plans.is_empty() {
+        settings.error("The target score cannot be reached within the limits of the profile.");
+        return 1;
+    }
+    if plans[0].changes.is_empty() {
+        settings.info("The judgement already reaches the target score.");
+        return 0;
+    }
+
+    for plan in plans.iter().take(limit) {
+        let code = plan.style.encode();
+        if settings.raw {
+            println!("{}", code);
+            continue;
+        }
+        let changes = plan
+            .changes
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{:>3} {:<6} {:<10} {:>6}  {}",
+            plan.points(),
+            if plan.points() == 1 {
+                "point"
+            } else {
+                "points"
+            },
+            code,
+            plan.score.format(format),
+            changes
+        );
+    }
+    if plans.len() > limit {
+        settings.info(&format!(
+            "{} of {} minimal changes shown, use --limit to show more.",
+            limit,
+            plans.len()
+        ));
+    }
+    0
+}
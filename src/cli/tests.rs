@@ -0,0 +1,310 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use servizio::{Category, OutputFormat, RecordWriter, ScoreFormat, ScoringProfile};
+
+use crate::cli::decode::{decode_all, is_batch, Options, Summary};
+use crate::cli::form::Form;
+use crate::cli::repl::Session;
+use crate::cli::{LegacyMode, Settings};
+use crate::create_clap_app;
+
+/// The settings of `servizio-cli --raw`.
+fn raw_settings() -> Settings {
+    Settings {
+        raw: true,
+        profile: ScoringProfile::default(),
+        score_format: ScoreFormat::default(),
+    }
+}
+
+#[test]
+fn batch_decoding() {
+    let settings = raw_settings();
+    let options = Options {
+        requested_value: None,
+        batch: true,
+        explain: false,
+    };
+    let codes = |codes: &[&str]| {
+        codes
+            .iter()
+            .map(|code| code.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Invalid codes do not stop the batch.
+    let mut writer = RecordWriter::new(Vec::new(), OutputFormat::Json, ScoreFormat::default());
+    let summary = decode_all(
+        codes(&["n4", "xx", "G13DM12"]).into_iter(),
+        &options,
+        &mut writer,
+        &settings,
+    );
+    assert_eq!(
+        summary,
+        Summary {
+            total: 3,
+            failures: 1,
+            exit_code: 1
+        }
+    );
+    assert_eq!(
+        summary.failure_message(),
+        Some("1 of 3 codes could not be decoded.".to_string())
+    );
+    let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("{\"code\":\"n4\","));
+    assert!(lines[1].starts_with("{\"code\":\"g13dm12\","));
+
+    // A single invalid line is still counted.
+    let mut writer = RecordWriter::new(Vec::new(), OutputFormat::Json, ScoreFormat::default());
+    let summary = decode_all(codes(&["xx"]).into_iter(), &options, &mut writer, &settings);
+    assert_eq!(
+        summary.failure_message(),
+        Some("1 of 1 codes could not be decoded.".to_string())
+    );
+    let summary = decode_all(codes(&["n4"]).into_iter(), &options, &mut writer, &settings);
+    assert_eq!(summary.failure_message(), None);
+    assert_eq!(summary.exit_code, 0);
+
+    // Standard input is a batch however many lines it has.
+    let batch = |args: &[&str]| {
+        let matches = create_clap_app().get_matches_from(args);
+        let decode = matches.subcommand_matches("decode").unwrap();
+        is_batch(&decode.values_of("code").unwrap())
+    };
+    assert!(batch(&["servizio-cli", "decode", "-"]));
+    assert!(batch(&["servizio-cli", "decode", "n4", "n5"]));
+    assert!(!batch(&["servizio-cli", "decode", "n4"]));
+}
+
+/// Checks that the legacy arguments and the subcommand give the same values
+/// to the options read by the subcommand.
+fn assert_same_options(legacy: &[&str], subcommand: &[&str], names: &[&str]) {
+    let legacy = create_clap_app().get_matches_from(legacy);
+    let matches = create_clap_app().get_matches_from(subcommand);
+    let (_, sub_matches) = matches.subcommand();
+    let sub_matches = sub_matches.unwrap();
+    for &name in names {
+        assert_eq!(
+            legacy
+                .values_of(name)
+                .map(|values| values.collect::<Vec<_>>()),
+            sub_matches
+                .values_of(name)
+                .map(|values| values.collect::<Vec<_>>()),
+            "{}",
+            name
+        );
+        assert_eq!(
+            legacy.is_present(name),
+            sub_matches.is_present(name),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn legacy_flags() {
+    let mode = |args: &[&str]| LegacyMode::from_matches(&create_clap_app().get_matches_from(args));
+
+    assert_eq!(mode(&["servizio-cli", "g13dm12"]), Some(LegacyMode::Decode));
+    assert_same_options(
+        &["servizio-cli", "g13dm12", "-v", "score", "--raw"],
+        &["servizio-cli", "decode", "g13dm12", "-v", "score", "--raw"],
+        &["code", "value", "raw"],
+    );
+
+    assert_eq!(
+        mode(&["servizio-cli", "--encode=bas=1,mov=3"]),
+        Some(LegacyMode::Encode)
+    );
+    assert_eq!(
+        mode(&["servizio-cli", "-e", "bas=1,mov=3"]),
+        Some(LegacyMode::Encode)
+    );
+    assert_same_options(
+        &["servizio-cli", "--encode=bas=1,mov=3"],
+        &["servizio-cli", "encode", "bas=1,mov=3"],
+        &["judgement"],
+    );
+
+    assert_eq!(
+        mode(&["servizio-cli", "--infile", "in.csv", "--outfile", "out.csv"]),
+        Some(LegacyMode::Process)
+    );
+    assert_same_options(
+        &[
+            "servizio-cli",
+            "--infile=in.csv",
+            "--outfile=out.csv",
+            "--headers",
+            "--column=1",
+            "--delimiter=;",
+            "--normalize",
+            "--suggest",
+        ],
+        &[
+            "servizio-cli",
+            "process",
+            "in.csv",
+            "out.csv",
+            "--headers",
+            "--column=1",
+            "--delimiter=;",
+            "--normalize",
+            "--suggest",
+        ],
+        &[
+            "infile",
+            "outfile",
+            "headers",
+            "column",
+            "delimiter",
+            "normalize",
+            "suggest",
+        ],
+    );
+
+    // The subcommands are not legacy modes.
+    assert_eq!(mode(&["servizio-cli", "decode", "g13dm12"]), None);
+    assert_eq!(mode(&["servizio-cli"]), None);
+
+    assert!(LegacyMode::Decode
+        .warning()
+        .contains("use `servizio-cli decode <code>`"));
+    assert!(LegacyMode::Encode
+        .warning()
+        .contains("use `servizio-cli encode <judgement>`"));
+    assert!(LegacyMode::Process
+        .warning()
+        .contains("use `servizio-cli process <infile> <outfile>`"));
+}
+
+/// Runs a REPL session over `lines` and returns its output and the codes left
+/// in its log.
+fn run_session(lines: &[&str]) -> (String, Vec<String>) {
+    let settings = raw_settings();
+    let mut session = Session::new(&settings, OutputFormat::Text, Vec::new());
+    for line in lines {
+        assert!(session.handle(line));
+    }
+    let log = session
+        .log()
+        .iter()
+        .map(|record| record.code.clone())
+        .collect();
+    (String::from_utf8(session.into_output()).unwrap(), log)
+}
+
+#[test]
+fn repl_session() {
+    let settings = raw_settings();
+    let mut session = Session::new(&settings, OutputFormat::Text, Vec::new());
+    assert!(!session.handle(":quit"));
+
+    // A judgement is encoded, anything else is decoded.
+    let (out, log) = run_session(&["bas=1,mov=3", "G13DM12", ""]);
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[1], "g13dm12");
+    assert!(out.contains(&format!("Code : {}", log[0])));
+
+    // The format applies to the following records.
+    let (out, log) = run_session(&[":format json", "g13dm12", ":format xml", ":format"]);
+    assert_eq!(log, ["g13dm12"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "Output format: json");
+    assert!(lines[1].starts_with("{\"code\":\"g13dm12\","));
+    assert!(lines[2].starts_with("Invalid format: "));
+    assert_eq!(lines[3], "Output format: json");
+
+    let (out, log) = run_session(&["g13dm12", ":clear", ":log"]);
+    assert!(log.is_empty());
+    assert!(out.contains("Session log cleared.\nThe session log is empty.\n"));
+
+    // The history is saved as csv.
+    let path = std::env::temp_dir().join("servizio-repl-session.csv");
+    let save = format!(":save {}", path.display());
+    let (out, _) = run_session(&["g13dm12", "n4", &save]);
+    assert!(out.contains(&format!("Saved 2 entries to {}", path.display())));
+    let saved = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines: Vec<&str> = saved.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("code,"));
+    assert!(lines[1].starts_with("g13dm12,"));
+    assert!(lines[2].starts_with("n4,"));
+
+    let (out, log) = run_session(&[":foo", "xx", "bas=x", ":save"]);
+    assert!(log.is_empty());
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "Unknown command: :foo (type :help for help)");
+    assert!(lines[1].starts_with("Invalid style code: "));
+    assert!(out.contains("\nInvalid judgement: "));
+    assert!(out.ends_with("Usage: :save <file>\n"));
+}
+
+#[test]
+fn form_keys() {
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    let profile = format!("[limits]\n{} = 2", Category::ALL[0].name());
+    let settings = Settings {
+        profile: ScoringProfile::from_toml_str(&profile).unwrap(),
+        ..raw_settings()
+    };
+    let mut form = Form::new(&settings);
+    let last = Category::ALL.len() - 1;
+
+    // The selection wraps around in both directions.
+    assert_eq!(form.handle(key(KeyCode::Up)), None);
+    assert_eq!(form.selected, last);
+    assert_eq!(form.category(), Category::ALL[last]);
+    form.handle(key(KeyCode::Down));
+    assert_eq!(form.selected, 0);
+    form.handle(key(KeyCode::BackTab));
+    form.handle(key(KeyCode::Tab));
+    assert_eq!(form.selected, 0);
+
+    // The values stay within the limits of the profile, and the code follows
+    // every change.
+    let code = |form: &Form| settings.profile.encode(&form.style).unwrap();
+    let first = Category::ALL[0];
+    form.handle(key(KeyCode::Left));
+    assert_eq!(form.style.get(first), 0);
+    assert_eq!(code(&form), "a0");
+    form.handle(key(KeyCode::Right));
+    assert_eq!(form.style.get(first), 1);
+    let one = code(&form);
+    assert_ne!(one, "a0");
+    form.handle(key(KeyCode::Char('+')));
+    form.handle(key(KeyCode::Right));
+    assert_eq!(form.style.get(first), 2);
+    let two = code(&form);
+    assert_ne!(two, one);
+    form.handle(key(KeyCode::Char('9')));
+    assert_eq!(form.style.get(first), 2);
+    form.handle(key(KeyCode::Char('-')));
+    assert_eq!(form.style.get(first), 1);
+    assert_eq!(code(&form), one);
+    form.handle(key(KeyCode::Backspace));
+    assert_eq!(code(&form), "a0");
+
+    // The penalties are limited by the encoding.
+    form.handle(key(KeyCode::Up));
+    form.set(u32::MAX);
+    assert_eq!(
+        form.style.get(Category::ALL[last]),
+        settings.profile.limit(Category::ALL[last])
+    );
+    assert_eq!(form.style.try_encode().unwrap(), code(&form));
+
+    assert_eq!(form.handle(key(KeyCode::Enter)), Some(true));
+    assert_eq!(form.handle(key(KeyCode::Esc)), Some(false));
+    assert_eq!(
+        form.handle(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+        Some(false)
+    );
+}
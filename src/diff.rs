@@ -0,0 +1,116 @@
+//! Comparison of several codes, e.g. those of the judges of one performance,
+//! category by category.
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+
+use crate::error::DiffError;
+use crate::output::Record;
+use crate::score::Score;
+use crate::style_code::Category;
+
+/// The values of one category in the compared codes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryDiff {
+  /// The category.
+  pub category: Category,
+  /// The value in each code, in the order of the codes.
+  pub values: Vec<u32>,
+  /// The change from the first code to the second, when two codes are
+  /// compared.
+  pub delta: Option<i64>,
+  /// The difference between the largest and the smallest value.
+  pub spread: u32,
+}
+
+impl CategoryDiff {
+  /// Whether every code has the same value.
+  pub fn agrees(&self) -> bool {
+    self.spread == 0
+  }
+}
+
+/// The comparison of two or more codes.
+///
+/// It serializes to a JSON object with the fields `codes`, `scores`,
+/// `score_delta` (only with two codes), `score_spread`, `categories` (the
+/// values of each category, by name) and `disagreements` (the names of the
+/// categories whose values differ).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Comparison {
+  /// The compared codes.
+  pub codes: Vec<String>,
+  /// The score of each code.
+  pub scores: Vec<Score>,
+  /// The change of score from the first code to the second, when two codes
+  /// are compared.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub score_delta: Option<Score>,
+  /// The difference between the highest and the lowest score.
+  pub score_spread: Score,
+  /// Every category, in the order of [`Category::ALL`].
+  #[serde(serialize_with = "serialize_categories")]
+  pub categories: Vec<CategoryDiff>,
+  /// The categories whose values differ, in the order of [`Category::ALL`].
+  pub disagreements: Vec<Category>,
+}
+
+/// Compares the judgements of `records`, which must be at least two.
+pub fn compare(records: &[Record]) -> Result<Comparison, DiffError> {
+  if records.len() < 2 {
+    return Err(DiffError::TooFewCodes(records.len()));
+  }
+  let pair = records.len() == 2;
+  let scores: Vec<Score> = records.iter().map(|record| record.score).collect();
+  let (min_score, max_score) = (scores.iter().min().unwrap(), scores.iter().max().unwrap());
+
+  let categories: Vec<CategoryDiff> = Category::ALL
+    .iter()
+    .map(|&category| {
+      let values: Vec<u32> = records
+        .iter()
+        .map(|record| record.style.get(category))
+        .collect();
+      let (min, max) = (values.iter().min().unwrap(), values.iter().max().unwrap());
+      CategoryDiff {
+        category,
+        delta: if pair {
+          Some(i64::from(values[1]) - i64::from(values[0]))
+        } else {
+          None
+        },
+        spread: max - min,
+        values,
+      }
+    })
+    .collect();
+
+  Ok(Comparison {
+    codes: records.iter().map(|record| record.code.clone()).collect(),
+    score_delta: if pair {
+      Some(scores[1] - scores[0])
+    } else {
+      None
+    },
+    score_spread: *max_score - *min_score,
+    scores,
+    disagreements: categories
+      .iter()
+      .filter(|diff| !diff.agrees())
+      .map(|diff| diff.category)
+      .collect(),
+    categories,
+  })
+}
+
+/// Serializes the categories as a map from their names to their values.
+fn serialize_categories<S: Serializer>(
+  categories: &[CategoryDiff],
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  let mut map = serializer.serialize_map(Some(categories.len()))?;
+  for diff in categories {
+    map.serialize_entry(diff.category.name(), &diff.values)?;
+  }
+  map.end()
+}
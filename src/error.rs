@@ -0,0 +1,601 @@
+//! Error types returned by the library.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::panel::AggregationMethod;
+use crate::score::Score;
+use crate::style_code::{Category, Layout};
+
+/// The reason why a Style Code could not be decoded.
+///
+/// Offsets are zero-based character positions in the decoded code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+  /// The code is empty.
+  Empty,
+  /// The code does not start with a letter.
+  InvalidFirstLetter {
+    /// Position of the offending character.
+    offset: usize,
+    /// The offending character.
+    found: char,
+  },
+  /// The first letter is not followed by the total of style points.
+  MissingPoints {
+    /// Position where the points were expected.
+    offset: usize,
+  },
+  /// The second letter is one of the letters excluded from its alphabet
+  /// (`i`, `o` and `z`).
+  ExcludedLetter {
+    /// Position of the offending character.
+    offset: usize,
+    /// The offending character.
+    found: char,
+  },
+  /// The letters encode a value that is too large for their layout.
+  LetterValueOutOfRange {
+    /// Position of the first letter.
+    offset: usize,
+    /// Number of letters in the code (1, 2 or 3).
+    letters: usize,
+    /// The value encoded by the letters.
+    value: u32,
+    /// The largest value the layout can hold.
+    max: u32,
+  },
+  /// The SOG digit is not between 0 and 3.
+  InvalidSog {
+    /// Position of the offending character.
+    offset: usize,
+    /// The offending character.
+    found: char,
+  },
+  /// The penalty character is not one of `0`-`9`, `a`-`h`, `j`-`l`.
+  InvalidPenalty {
+    /// Position of the offending character.
+    offset: usize,
+    /// The offending character.
+    found: char,
+  },
+  /// The total of style points is lower than the sum of the categories
+  /// encoded by the letters.
+  PointsBelowCategories {
+    /// Position of the points total.
+    offset: usize,
+    /// The points total.
+    points: u32,
+    /// The sum of the categories encoded by the letters.
+    categories: u32,
+  },
+  /// The total of style points would require more than 3 BAS points.
+  PointsAboveMaximum {
+    /// Position of the points total.
+    offset: usize,
+    /// The points total.
+    points: u32,
+    /// The largest points total allowed by the letters.
+    max: u32,
+  },
+  /// The code continues after a complete Style Code.
+  TrailingCharacters {
+    /// Position of the first unexpected character.
+    offset: usize,
+    /// The first unexpected character.
+    found: char,
+  },
+  /// The code ends with the check separator `-`, without a check character.
+  MissingCheckCharacter {
+    /// Position where the check character was expected.
+    offset: usize,
+  },
+  /// The check character does not match the rest of the code: the code was
+  /// probably mistyped.
+  CheckMismatch {
+    /// Position of the check character.
+    offset: usize,
+    /// The check character in the code.
+    found: char,
+    /// The check character computed from the rest of the code.
+    expected: char,
+  },
+}
+
+impl fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DecodeError::Empty => write!(f, "the code is empty"),
+      DecodeError::InvalidFirstLetter { offset, found } => write!(
+        f,
+        "invalid character '{}' at offset {}: a code must start with a letter",
+        found, offset
+      ),
+      DecodeError::MissingPoints { offset } => write!(
+        f,
+        "missing points total at offset {}: expected one or two digits",
+        offset
+      ),
+      DecodeError::ExcludedLetter { offset, found } => write!(
+        f,
+        "letter '{}' at offset {} is not allowed as second letter (i, o and z are excluded)",
+        found, offset
+      ),
+      DecodeError::LetterValueOutOfRange {
+        offset,
+        letters,
+        value,
+        max,
+      } => write!(
+        f,
+        "the {}-letter code at offset {} encodes the value {}, but the maximum is {}",
+        letters, offset, value, max
+      ),
+      DecodeError::InvalidSog { offset, found } => write!(
+        f,
+        "invalid SOG value '{}' at offset {}: expected a digit from 0 to 3",
+        found, offset
+      ),
+      DecodeError::InvalidPenalty { offset, found } => write!(
+        f,
+        "invalid penalty character '{}' at offset {}: expected one of 0-9, a-h, j-l",
+        found, offset
+      ),
+      DecodeError::PointsBelowCategories {
+        offset,
+        points,
+        categories,
+      } => write!(
+        f,
+        "points total {} at offset {} is lower than the sum of the categories ({})",
+        points, offset, categories
+      ),
+      DecodeError::PointsAboveMaximum {
+        offset,
+        points,
+        max,
+      } => write!(
+        f,
+        "points total {} at offset {} is higher than the maximum allowed by the letters ({})",
+        points, offset, max
+      ),
+      DecodeError::TrailingCharacters { offset, found } => {
+        write!(f, "unexpected character '{}' at offset {}", found, offset)
+      }
+      DecodeError::MissingCheckCharacter { offset } => {
+        write!(f, "missing check character at offset {}", offset)
+      }
+      DecodeError::CheckMismatch {
+        offset,
+        found,
+        expected,
+      } => write!(
+        f,
+        "check character '{}' at offset {} does not match the code (expected '{}'): the code \
+         was probably mistyped",
+        found, offset, expected
+      ),
+    }
+  }
+}
+
+impl Error for DecodeError {}
+
+/// The reason why a code is not valid under a scoring profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CodeError {
+  /// The code could not be decoded.
+  Decode(DecodeError),
+  /// The judgement is outside the limits of the profile.
+  Limit(EncodeError),
+}
+
+impl fmt::Display for CodeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      CodeError::Decode(err) => err.fmt(f),
+      CodeError::Limit(err) => err.fmt(f),
+    }
+  }
+}
+
+impl Error for CodeError {}
+
+impl From<DecodeError> for CodeError {
+  fn from(err: DecodeError) -> CodeError {
+    CodeError::Decode(err)
+  }
+}
+
+impl From<EncodeError> for CodeError {
+  fn from(err: EncodeError) -> CodeError {
+    CodeError::Limit(err)
+  }
+}
+
+/// The reason why a judgement could not be encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EncodeError {
+  /// A category is outside its allowed range.
+  OutOfRange {
+    /// The offending category.
+    category: Category,
+    /// Its value.
+    value: u32,
+    /// The largest value allowed in the category.
+    max: u32,
+  },
+  /// An adjustment would make a category negative.
+  Negative {
+    /// The offending category.
+    category: Category,
+    /// The value it would have.
+    value: i64,
+  },
+  /// The judgement cannot be represented in the requested layout.
+  LayoutTooSmall {
+    /// The requested layout.
+    layout: Layout,
+    /// The shortest layout that can represent the judgement.
+    minimum: Layout,
+  },
+}
+
+impl fmt::Display for EncodeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      EncodeError::OutOfRange {
+        category,
+        value,
+        max,
+      } => write!(
+        f,
+        "{} is {}, but it must be between 0 and {}",
+        category, value, max
+      ),
+      EncodeError::Negative { category, value } => {
+        write!(
+          f,
+          "{} would be {}, but it cannot be negative",
+          category, value
+        )
+      }
+      EncodeError::LayoutTooSmall { layout, minimum } => write!(
+        f,
+        "the judgement cannot be written as a {}-letter code, it needs at least {} letters",
+        layout, minimum
+      ),
+    }
+  }
+}
+
+impl Error for EncodeError {}
+
+/// The reason why a judgement string could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JudgementError {
+  /// An entry is not in the `category=points` form.
+  MalformedEntry(String),
+  /// An entry names an unknown category.
+  UnknownCategory(String),
+  /// The points of an entry are not a non-negative integer.
+  InvalidValue {
+    /// The category of the entry.
+    category: Category,
+    /// The text that could not be parsed.
+    value: String,
+  },
+}
+
+impl fmt::Display for JudgementError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      JudgementError::MalformedEntry(entry) => {
+        write!(f, "malformed entry '{}': expected category=points", entry)
+      }
+      JudgementError::UnknownCategory(name) => write!(f, "unknown category '{}'", name),
+      JudgementError::InvalidValue { category, value } => {
+        write!(f, "invalid value '{}' for {}", value, category)
+      }
+    }
+  }
+}
+
+impl Error for JudgementError {}
+
+/// The reason why a search constraint could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SearchError {
+  /// The constraint is not in the `field<op>value` form.
+  MalformedConstraint(String),
+  /// The constraint names an unknown field.
+  UnknownField(String),
+  /// The operator cannot be used with the field, e.g. `<` with `code`.
+  InvalidOperator {
+    /// The field.
+    field: String,
+    /// The operator.
+    operator: String,
+  },
+  /// The value cannot be compared with the field.
+  InvalidValue {
+    /// The field.
+    field: String,
+    /// The text that could not be parsed.
+    value: String,
+  },
+}
+
+impl fmt::Display for SearchError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SearchError::MalformedConstraint(constraint) => write!(
+        f,
+        "malformed constraint '{}': expected e.g. score=7.5 or mov>=2",
+        constraint
+      ),
+      SearchError::UnknownField(name) => write!(f, "unknown field '{}'", name),
+      SearchError::InvalidOperator { field, operator } => {
+        write!(f, "operator '{}' cannot be used with {}", operator, field)
+      }
+      SearchError::InvalidValue { field, value } => {
+        write!(f, "invalid value '{}' for {}", value, field)
+      }
+    }
+  }
+}
+
+impl Error for SearchError {}
+
+/// The reason why codes could not be compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiffError {
+  /// There are fewer than two codes.
+  TooFewCodes(usize),
+}
+
+impl fmt::Display for DiffError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DiffError::TooFewCodes(codes) => {
+        write!(f, "at least 2 codes are needed, but there are {}", codes)
+      }
+    }
+  }
+}
+
+impl Error for DiffError {}
+
+/// The reason why the judgements of a panel could not be aggregated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PanelError {
+  /// The panel has fewer judges than the method needs.
+  TooFewJudges {
+    /// The aggregation method.
+    method: AggregationMethod,
+    /// The number of judges.
+    judges: usize,
+    /// The smallest number of judges the method needs.
+    minimum: usize,
+  },
+}
+
+impl fmt::Display for PanelError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PanelError::TooFewJudges {
+        method,
+        judges,
+        minimum,
+      } => write!(
+        f,
+        "the {} method needs at least {} judges, but there are {}",
+        method, minimum, judges
+      ),
+    }
+  }
+}
+
+impl Error for PanelError {}
+
+/// The reason why the agreement of a panel could not be measured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AgreementError {
+  /// There are no performances.
+  NoPerformances,
+  /// There are fewer than two judges.
+  TooFewJudges(usize),
+  /// A performance does not have a judgement from every judge.
+  MissingJudgements {
+    /// The zero-based index of the performance.
+    performance: usize,
+    /// The number of judgements of the performance.
+    judgements: usize,
+    /// The number of judges.
+    judges: usize,
+  },
+}
+
+impl fmt::Display for AgreementError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AgreementError::NoPerformances => write!(f, "there are no performances"),
+      AgreementError::TooFewJudges(judges) => {
+        write!(f, "at least 2 judges are needed, but there are {}", judges)
+      }
+      AgreementError::MissingJudgements {
+        performance,
+        judgements,
+        judges,
+      } => write!(
+        f,
+        "performance {} has {} judgements, but there are {} judges",
+        performance, judgements, judges
+      ),
+    }
+  }
+}
+
+impl Error for AgreementError {}
+
+/// The reason why a `csv` file could not be processed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProcessError {
+  /// The input and the output are the same file.
+  SameFile(String),
+  /// The input file could not be opened.
+  OpenInput {
+    /// The path of the file.
+    path: String,
+    /// Why it could not be opened.
+    reason: String,
+  },
+  /// The output file could not be created.
+  CreateOutput {
+    /// The path of the file.
+    path: String,
+    /// Why it could not be created.
+    reason: String,
+  },
+  /// A record of the input file could not be read.
+  Read {
+    /// The path of the file.
+    path: String,
+    /// The zero-based line of the record.
+    line: usize,
+  },
+  /// A processed record could not be written to the output file.
+  Write {
+    /// The path of the file.
+    path: String,
+    /// The zero-based line of the record.
+    line: usize,
+  },
+}
+
+impl fmt::Display for ProcessError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ProcessError::SameFile(path) => write!(
+        f,
+        "reading and writing to the same file is not supported: {}",
+        path
+      ),
+      ProcessError::OpenInput { path, reason } => {
+        write!(f, "cannot open csv file: {}: {}", path, reason)
+      }
+      ProcessError::CreateOutput { path, reason } => {
+        write!(f, "cannot create csv file: {}: {}", path, reason)
+      }
+      ProcessError::Read { path, line } => {
+        write!(f, "cannot read line {} from csv file: {}", line, path)
+      }
+      ProcessError::Write { path, line } => write!(
+        f,
+        "cannot write processed line {} to csv file: {}",
+        line, path
+      ),
+    }
+  }
+}
+
+impl Error for ProcessError {}
+
+/// The reason why a score could not be computed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScoreError {
+  /// The score is below the floor of a [`ScorePolicy::Reject`] policy.
+  ///
+  /// [`ScorePolicy::Reject`]: crate::score::ScorePolicy::Reject
+  BelowFloor {
+    /// The score.
+    score: Score,
+    /// The floor.
+    floor: Score,
+  },
+  /// The score is too large to be represented, because of the weights of the
+  /// profile or the values of the judgement.
+  Overflow,
+}
+
+impl fmt::Display for ScoreError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ScoreError::BelowFloor { score, floor } => {
+        write!(f, "score {} is below the floor {}", score, floor)
+      }
+      ScoreError::Overflow => write!(f, "score is out of range"),
+    }
+  }
+}
+
+impl Error for ScoreError {}
+
+/// A string could not be parsed as a [`Score`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseScoreError(pub String);
+
+impl fmt::Display for ParseScoreError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "invalid score '{}'", self.0)
+  }
+}
+
+impl Error for ParseScoreError {}
+
+/// The reason why a scoring profile could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProfileError {
+  /// The profile file could not be read.
+  Io(String),
+  /// The profile file is not valid TOML or JSON, or has unknown keys.
+  Parse(String),
+  /// The profile names an unknown category.
+  UnknownCategory(String),
+  /// A value in the profile is invalid (the key and the value).
+  InvalidValue(String, String),
+  /// The limit of a category is higher than what a Style Code can represent.
+  LimitTooHigh {
+    /// The category.
+    category: Category,
+    /// The limit in the profile.
+    limit: u32,
+    /// The largest value a code can represent for the category.
+    max: u32,
+  },
+}
+
+impl fmt::Display for ProfileError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ProfileError::Io(msg) => write!(f, "cannot read profile {}", msg),
+      ProfileError::Parse(msg) => write!(f, "invalid profile: {}", msg),
+      ProfileError::UnknownCategory(name) => write!(f, "unknown category '{}' in profile", name),
+      ProfileError::InvalidValue(key, value) => {
+        write!(f, "invalid value '{}' for '{}' in profile", value, key)
+      }
+      ProfileError::LimitTooHigh {
+        category,
+        limit,
+        max,
+      } => write!(
+        f,
+        "the limit {} for {} is higher than the maximum a code can represent ({})",
+        limit, category, max
+      ),
+    }
+  }
+}
+
+impl Error for ProfileError {}
@@ -0,0 +1,394 @@
+//! Step-by-step explanations of how codes are decoded and encoded.
+//!
+//! The explanations repeat the arithmetic of [`StyleCode::decode`] and
+//! [`StyleCode::encode`] in a form that can be checked by hand.
+
+use std::fmt;
+
+use crate::check::CHECK_SEPARATOR;
+use crate::error::{DecodeError, EncodeError};
+use crate::style_code::{Category, Layout, StyleCode, ALPHABET_23, ALPHABET_26, PENALTIES_CODE};
+
+/// A step-by-step explanation, printed one step per line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+  /// The steps, in order.
+  pub steps: Vec<String>,
+}
+
+impl fmt::Display for Explanation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for step in &self.steps {
+      writeln!(f, "{}", step)?;
+    }
+    Ok(())
+  }
+}
+
+/// The categories packed in the letters of each layout, with their radices.
+fn radices(layout: Layout) -> [(Category, u32); 6] {
+  use Category::*;
+  match layout {
+    Layout::One => [(Mov, 3), (Din, 3), (Gcc, 3), (Com, 1), (Sapd, 1), (Dif, 1)],
+    Layout::Two => [(Mov, 4), (Din, 4), (Gcc, 4), (Com, 2), (Sapd, 2), (Dif, 2)],
+    Layout::Three => [(Mov, 4), (Din, 4), (Gcc, 4), (Com, 4), (Sapd, 4), (Dif, 4)],
+  }
+}
+
+fn index(alphabet: &str, letter: char) -> u32 {
+  alphabet.find(letter).unwrap() as u32
+}
+
+/// Explains how `code` is decoded: the layout, the value of each letter, the
+/// combined value and how it is split into categories, how BAS is derived
+/// from the points total and how the SOG and PEN suffix is read.
+///
+/// Returns the same error as [`StyleCode::decode`] if the code is invalid.
+pub fn explain_decode(code: &str) -> Result<Explanation, DecodeError> {
+  let (style, layout) = StyleCode::decode_with_layout(code)?;
+  let all_chars: Vec<char> = code.chars().map(|c| c.to_ascii_lowercase()).collect();
+  let separator = all_chars.iter().position(|&c| c == CHECK_SEPARATOR);
+  let chars = &all_chars[..separator.unwrap_or(all_chars.len())];
+  let mut steps = Vec::new();
+
+  steps.push(format!(
+    "Code '{}': {}-letter layout.",
+    code.to_ascii_lowercase(),
+    layout
+  ));
+
+  let first = index(ALPHABET_26, chars[0]);
+  steps.push(format!(
+    "First letter '{}' (offset 0): index {} in ALPHABET_26 ({}).",
+    chars[0], first, ALPHABET_26
+  ));
+
+  let digits = chars[1..]
+    .iter()
+    .take(2)
+    .take_while(|c| c.is_ascii_digit())
+    .count();
+  let points: String = chars[1..1 + digits].iter().collect();
+  steps.push(format!("Points total (offset 1): {}.", points));
+  let mut pos = 1 + digits;
+
+  let value = match layout {
+    Layout::One => first,
+    Layout::Two => {
+      let second = index(ALPHABET_23, chars[pos]);
+      steps.push(format!(
+        "Second letter '{}' (offset {}): index {} in ALPHABET_23 ({}, without i, o and z).",
+        chars[pos], pos, second, ALPHABET_23
+      ));
+      pos += 1;
+      let value = first * 23 + second;
+      steps.push(format!(
+        "Combined value: {} * 23 + {} = {}.",
+        first, second, value
+      ));
+      value
+    }
+    Layout::Three => {
+      let second = index(ALPHABET_23, chars[pos]);
+      steps.push(format!(
+        "Second letter '{}' (offset {}): index {} in ALPHABET_23 ({}, without i, o and z).",
+        chars[pos], pos, second, ALPHABET_23
+      ));
+      let third = index(ALPHABET_26, chars[pos + 1]);
+      steps.push(format!(
+        "Third letter '{}' (offset {}): index {} in ALPHABET_26.",
+        chars[pos + 1],
+        pos + 1,
+        third
+      ));
+      pos += 2;
+      let value = (first * 23 + second) * 26 + third;
+      steps.push(format!(
+        "Combined value: {} * 23 * 26 + {} * 26 + {} = {}.",
+        first, second, third, value
+      ));
+      value
+    }
+  };
+
+  // Split the combined value into categories, most significant first.
+  let radices: Vec<(Category, u32)> = radices(layout)
+    .iter()
+    .copied()
+    .filter(|&(_, radix)| radix > 1)
+    .collect();
+  let bases: Vec<String> = radices.iter().map(|(_, r)| r.to_string()).collect();
+  let names: Vec<String> = radices.iter().map(|(c, _)| c.to_string()).collect();
+  steps.push(format!(
+    "The value holds {} as digits in bases {}.",
+    names.join(", "),
+    bases.join(", ")
+  ));
+  let mut remainder = value;
+  for (i, &(category, _)) in radices.iter().enumerate() {
+    let rest = &radices[i + 1..];
+    if rest.is_empty() {
+      steps.push(format!("{} = {} (last remainder).", category, remainder));
+      break;
+    }
+    let divisor: u32 = rest.iter().map(|(_, r)| r).product();
+    let factors: Vec<String> = rest.iter().map(|(_, r)| r.to_string()).collect();
+    steps.push(format!(
+      "{} = {} / {} ({}) = {}, remainder {}.",
+      category,
+      remainder,
+      divisor,
+      factors.join("*"),
+      remainder / divisor,
+      remainder % divisor
+    ));
+    remainder %= divisor;
+  }
+  if layout == Layout::One {
+    steps.push("COM, SAPD and DIF are always 0 in a 1-letter code.".to_string());
+  }
+
+  let others = style.mov + style.din + style.com + style.sapd + style.gcc + style.dif;
+  steps.push(format!(
+    "BAS = points total - (MOV + DIN + COM + SAPD + GCC + DIF) = {} - {} = {}.",
+    points, others, style.bas
+  ));
+
+  // SOG and PEN suffix.
+  if layout == Layout::One && chars.get(pos) == Some(&'z') {
+    steps.push(format!(
+      "Separator 'z' (offset {}): a SOG/PEN suffix follows the 1-letter code.",
+      pos
+    ));
+    pos += 1;
+  }
+  match chars.get(pos) {
+    None => steps.push("No SOG/PEN suffix: SOG = 0, PEN = 0.".to_string()),
+    Some(&sog) => {
+      steps.push(format!(
+        "SOG digit '{}' (offset {}): SOG = {}.",
+        sog, pos, style.sog
+      ));
+      match chars.get(pos + 1) {
+        None => steps.push("No penalty character: PEN = 0.".to_string()),
+        Some(&pen) => steps.push(format!(
+          "Penalty character '{}' (offset {}): index {} in {}, PEN = {}.",
+          pen,
+          pos + 1,
+          index(PENALTIES_CODE, pen),
+          PENALTIES_CODE,
+          style.pen
+        )),
+      }
+    }
+  }
+
+  if let Some(separator) = separator {
+    steps.push(format!(
+      "Check character '{}' (offset {}): matches the code before '{}'.",
+      all_chars[separator + 1],
+      separator + 1,
+      CHECK_SEPARATOR
+    ));
+  }
+
+  Ok(Explanation { steps })
+}
+
+/// Explains how `style` is encoded: which layout was chosen and why the
+/// shorter ones were not possible, the packed value and how it is split into
+/// letters, and when the `z` separator and the penalty character are
+/// appended.
+///
+/// Returns the same error as [`StyleCode::try_encode`] if the judgement is
+/// invalid.
+pub fn explain_encode(style: &StyleCode) -> Result<Explanation, EncodeError> {
+  explain_encode_with_layout(style, style.layout())
+}
+
+/// Explains how `style` is encoded with the given layout, like
+/// [`explain_encode`] for [`StyleCode::try_encode_with_layout`].
+pub fn explain_encode_with_layout(
+  style: &StyleCode,
+  layout: Layout,
+) -> Result<Explanation, EncodeError> {
+  let code = style.try_encode_with_layout(layout)?;
+  let minimum = style.layout();
+  let mut steps = Vec::new();
+
+  let values: Vec<String> = Category::ALL
+    .iter()
+    .map(|&category| format!("{}={}", category.name(), style.get(category)))
+    .collect();
+  steps.push(format!("Judgement: {}.", values.join(",")));
+  let points = style.bas + style.mov + style.din + style.com + style.sapd + style.gcc + style.dif;
+  steps.push(format!(
+    "Points total: BAS + MOV + DIN + COM + SAPD + GCC + DIF = {}.",
+    points
+  ));
+
+  // The conditions of each layout, as checked by `StyleCode::layout`.
+  let one_letter = [
+    (
+      "COM + SAPD + DIF",
+      style.com + style.sapd + style.dif,
+      "must be 0",
+      style.com + style.sapd + style.dif == 0,
+    ),
+    (
+      "MOV + DIN + GCC",
+      style.mov + style.din + style.gcc,
+      "must be below 6",
+      style.mov + style.din + style.gcc < 6,
+    ),
+    ("MOV", style.mov, "must be below 3", style.mov < 3),
+    ("DIN", style.din, "must be below 3", style.din < 3),
+    ("GCC", style.gcc, "must be below 3", style.gcc < 3),
+  ];
+  let two_letters = [
+    ("COM", style.com, "must be below 2", style.com < 2),
+    ("SAPD", style.sapd, "must be below 2", style.sapd < 2),
+    ("DIF", style.dif, "must be below 2", style.dif < 2),
+  ];
+  for (letters, conditions) in [(1, &one_letter[..]), (2, &two_letters[..])].iter() {
+    let failed: Vec<String> = conditions
+      .iter()
+      .filter(|(_, _, _, ok)| !ok)
+      .map(|(name, value, rule, _)| format!("{} is {} ({})", name, value, rule))
+      .collect();
+    if failed.is_empty() {
+      let verb = if layout == minimum {
+        "chosen"
+      } else {
+        "possible"
+      };
+      steps.push(format!(
+        "{}-letter layout {}: all its conditions hold.",
+        letters, verb
+      ));
+      break;
+    }
+    steps.push(format!(
+      "{}-letter layout not possible: {}.",
+      letters,
+      failed.join(", ")
+    ));
+  }
+  if minimum == Layout::Three {
+    steps.push("3-letter layout chosen: it can represent any judgement.".to_string());
+  } else if layout > minimum {
+    steps.push(format!(
+      "{}-letter layout requested instead: it can represent any judgement the {}-letter layout can.",
+      layout, minimum
+    ));
+  }
+
+  let letters: Vec<char> = code
+    .chars()
+    .filter(|c| c.is_ascii_alphabetic())
+    .take(layout.letters())
+    .collect();
+  match layout {
+    Layout::One => {
+      let value = (style.mov * 3 + style.din) * 3 + style.gcc;
+      steps.push(format!(
+        "Packed value: (MOV * 3 + DIN) * 3 + GCC = ({} * 3 + {}) * 3 + {} = {}.",
+        style.mov, style.din, style.gcc, value
+      ));
+      steps.push(format!(
+        "First letter: index {} in ALPHABET_26 ({}) is '{}'.",
+        value, ALPHABET_26, letters[0]
+      ));
+    }
+    Layout::Two => {
+      let value =
+        ((((style.mov * 4 + style.din) * 4 + style.gcc) * 2 + style.com) * 2 + style.sapd) * 2
+          + style.dif;
+      steps.push(format!(
+        "Packed value: ((((MOV * 4 + DIN) * 4 + GCC) * 2 + COM) * 2 + SAPD) * 2 + DIF \
+         = (((({} * 4 + {}) * 4 + {}) * 2 + {}) * 2 + {}) * 2 + {} = {}.",
+        style.mov, style.din, style.gcc, style.com, style.sapd, style.dif, value
+      ));
+      steps.push(format!(
+        "First letter: {} / 23 = {}, index {} in ALPHABET_26 ({}) is '{}'.",
+        value,
+        value / 23,
+        value / 23,
+        ALPHABET_26,
+        letters[0]
+      ));
+      steps.push(format!(
+        "Second letter: {} % 23 = {}, index {} in ALPHABET_23 ({}) is '{}'.",
+        value,
+        value % 23,
+        value % 23,
+        ALPHABET_23,
+        letters[1]
+      ));
+    }
+    Layout::Three => {
+      let value =
+        ((((style.mov * 4 + style.din) * 4 + style.gcc) * 4 + style.com) * 4 + style.sapd) * 4
+          + style.dif;
+      steps.push(format!(
+        "Packed value: ((((MOV * 4 + DIN) * 4 + GCC) * 4 + COM) * 4 + SAPD) * 4 + DIF \
+         = (((({} * 4 + {}) * 4 + {}) * 4 + {}) * 4 + {}) * 4 + {} = {}.",
+        style.mov, style.din, style.gcc, style.com, style.sapd, style.dif, value
+      ));
+      let remainder = value % (23 * 26);
+      steps.push(format!(
+        "First letter: {} / (23 * 26) = {}, index {} in ALPHABET_26 ({}) is '{}'.",
+        value,
+        value / (23 * 26),
+        value / (23 * 26),
+        ALPHABET_26,
+        letters[0]
+      ));
+      steps.push(format!(
+        "Second letter: ({} % (23 * 26)) / 26 = {} / 26 = {}, index {} in ALPHABET_23 ({}) is '{}'.",
+        value,
+        remainder,
+        remainder / 26,
+        remainder / 26,
+        ALPHABET_23,
+        letters[1]
+      ));
+      steps.push(format!(
+        "Third letter: {} % 26 = {}, index {} in ALPHABET_26 is '{}'.",
+        remainder,
+        remainder % 26,
+        remainder % 26,
+        letters[2]
+      ));
+    }
+  }
+  steps.push(format!(
+    "The points total {} is written after the first letter.",
+    points
+  ));
+
+  if style.sog + style.pen == 0 {
+    steps.push("SOG and PEN are 0: no suffix.".to_string());
+  } else {
+    if layout == Layout::One {
+      steps.push(
+        "Separator 'z' appended: in a 1-letter code the SOG digit would otherwise be read as \
+         part of the points total."
+          .to_string(),
+      );
+    }
+    steps.push(format!("SOG digit '{}' appended.", style.sog));
+    if style.pen == 0 {
+      steps.push("PEN is 0: no penalty character.".to_string());
+    } else {
+      let pen = PENALTIES_CODE.chars().nth(style.pen as usize).unwrap();
+      steps.push(format!(
+        "Penalty character '{}' appended: index {} in {}.",
+        pen, style.pen, PENALTIES_CODE
+      ));
+    }
+  }
+  steps.push(format!("Code: {}.", code));
+
+  Ok(Explanation { steps })
+}
@@ -1,98 +1,226 @@
-use csv;
+//! Bulk decoding of style codes stored in `csv` files.
 
+use crate::error::ProcessError;
+use crate::profile::ScoringProfile;
+use crate::score::ScoreFormat;
 use crate::style_code::StyleCode;
+use crate::suggest::suggest;
 
-struct CsvProcessor {
+/// Decodes the style codes in one column of a `csv` file, record by record.
+///
+/// Each processed record is the input record with the score and the ten
+/// decoded values appended.
+pub struct CsvProcessor {
   has_headers: bool,
   column_index: Option<usize>,
+  profile: ScoringProfile,
+  score_format: ScoreFormat,
+  normalize: bool,
+  suggestions: bool,
 }
 
+/// Decodes the style codes in `infile` and writes the results to `outfile`.
+///
+/// Both files use `delimiter` as field separator. Invalid codes do not stop
+/// the processing (see [`CsvProcessor::process_record`]).
 pub fn process_file(
   infile: &str,
   outfile: &str,
   delimiter: char,
-  has_headers: bool,
-  column_index: Option<usize>,
-) -> Result<(), (String, i32)> {
-  let mut processor = CsvProcessor {
-    has_headers,
-    column_index,
-  };
-
+  mut processor: CsvProcessor,
+) -> Result<(), ProcessError> {
   if infile == outfile {
-    return Err((
-      format!(
-        "Reading and writing to the same file is not supported: {}",
-        infile
-      ),
-      1001,
-    ));
+    return Err(ProcessError::SameFile(infile.to_string()));
   }
 
-  let mut reader = match csv::ReaderBuilder::new()
+  let mut reader = csv::ReaderBuilder::new()
     .has_headers(false)
     .delimiter(delimiter as u8)
     .from_path(infile)
-  {
-    Ok(r) => r,
-    Err(_) => return Err((format!("Error opening csv file: {}", infile), 1002)),
-  };
+    .map_err(|err| ProcessError::OpenInput {
+      path: infile.to_string(),
+      reason: err.to_string(),
+    })?;
 
-  let mut writer = match csv::WriterBuilder::new()
+  let mut writer = csv::WriterBuilder::new()
     .has_headers(false)
     .delimiter(delimiter as u8)
     .from_path(outfile)
-  {
-    Ok(r) => r,
-    Err(_) => return Err((format!("Error opening csv file: {}", outfile), 1003)),
+    .map_err(|err| ProcessError::CreateOutput {
+      path: outfile.to_string(),
+      reason: err.to_string(),
+    })?;
+  for (line, result) in reader.records().enumerate() {
+    let record = result.map_err(|_| ProcessError::Read {
+      path: infile.to_string(),
+      line,
+    })?;
+    writer
+      .write_record(&processor.process_record(&record))
+      .map_err(|_| ProcessError::Write {
+        path: outfile.to_string(),
+        line,
+      })?;
+  }
+  Ok(())
+}
+
+/// The judgements of a panel of judges, read from a `csv` file by
+/// [`read_panels`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanelFile {
+  /// The names of the judges: the headers of their columns, or `Judge 1`,
+  /// `Judge 2`, ... without headers.
+  pub judges: Vec<String>,
+  /// The judgements of each performance, one per judge.
+  pub panels: Vec<Vec<StyleCode>>,
+  /// The records that were skipped because a code is missing or invalid: the
+  /// zero-based line and the reason.
+  pub skipped: Vec<(usize, String)>,
+}
+
+/// Reads a `csv` file with one record per performance and the codes of the
+/// judges in the columns `columns` (zero-based). If `columns` is `None`, every
+/// column but the first, which usually names the performance, holds codes.
+///
+/// Codes are decoded as by [`CsvProcessor`]: records with a code that is
+/// missing, invalid or outside the limits of `profile` are skipped.
+pub fn read_panels(
+  infile: &str,
+  delimiter: char,
+  has_headers: bool,
+  columns: Option<&[usize]>,
+  profile: &ScoringProfile,
+) -> Result<PanelFile, ProcessError> {
+  let mut reader = csv::ReaderBuilder::new()
+    .has_headers(false)
+    .flexible(true)
+    .delimiter(delimiter as u8)
+    .from_path(infile)
+    .map_err(|err| ProcessError::OpenInput {
+      path: infile.to_string(),
+      reason: err.to_string(),
+    })?;
+
+  let mut file = PanelFile {
+    judges: Vec::new(),
+    panels: Vec::new(),
+    skipped: Vec::new(),
   };
+  let mut columns: Option<Vec<usize>> = columns.map(<[usize]>::to_vec);
   for (line, result) in reader.records().enumerate() {
-    let line_process_result = match result {
-      Ok(val) => processor.process_record(&val),
-      Err(_) => {
-        return Err((
-          format!("Error reading line {} from csv file: {}", line, infile),
-          1004,
-        ))
+    let record = result.map_err(|_| ProcessError::Read {
+      path: infile.to_string(),
+      line,
+    })?;
+    let columns = columns.get_or_insert_with(|| (1..record.len()).collect());
+    if line == 0 {
+      file.judges = columns
+        .iter()
+        .enumerate()
+        .map(|(i, &column)| match record.get(column) {
+          Some(header) if has_headers => header.trim().to_string(),
+          _ => format!("Judge {}", i + 1),
+        })
+        .collect();
+      if has_headers {
+        continue;
       }
-    };
-
-    let line_write_result = match line_process_result {
-      Ok(processed_line) => writer.write_record(&processed_line),
-      Err((msg, err_code)) => {
-        return Err((
-          format!(
-            "Error processing line {} from csv file: {}\n  {}",
-            line, infile, msg
-          ),
-          err_code,
-        ))
-      }
-    };
-
-    if line_write_result.is_err() {
-      return Err((
-        format!(
-          "Error writing processed line {} to csv file: {}",
-          line, outfile
-        ),
-        1005,
-      ));
+    }
+
+    let panel: Result<Vec<StyleCode>, String> = columns
+      .iter()
+      .map(|&column| {
+        decode_field(&record, column, profile)
+          .map(|(_, decoded)| decoded)
+          .map_err(|reason| match record.get(column) {
+            Some(code) => format!("invalid code '{}': {}", code.trim(), reason),
+            None => reason,
+          })
+      })
+      .collect();
+    match panel {
+      Ok(panel) => file.panels.push(panel),
+      Err(reason) => file.skipped.push((line, reason)),
     }
   }
+  Ok(file)
+}
 
-  Ok(())
+/// Decodes the code in `column` of `record` with `profile`, returning the
+/// trimmed code and its judgement, or the reason why it is not a valid code.
+fn decode_field<'r>(
+  record: &'r csv::StringRecord,
+  column: usize,
+  profile: &ScoringProfile,
+) -> Result<(&'r str, StyleCode), String> {
+  let code = match record.get(column) {
+    Some(code) => code.trim(),
+    None => return Err(format!("missing column {}", column)),
+  };
+  match profile.decode(code) {
+    Ok(decoded) => Ok((code, decoded)),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
 impl CsvProcessor {
-  fn process_record(
-    &mut self,
-    record: &csv::StringRecord,
-  ) -> Result<csv::StringRecord, (String, i32)> {
-    assert!(!record.is_empty());
+  /// Creates a new processor.
+  ///
+  /// If `has_headers` is set, the first record is treated as column headers
+  /// and the names of the decoded columns are appended to it. `column_index`
+  /// is the zero-based index of the column holding the codes; if it is `None`,
+  /// the last column of the first record is used.
+  pub fn new(has_headers: bool, column_index: Option<usize>) -> CsvProcessor {
+    CsvProcessor {
+      has_headers,
+      column_index,
+      profile: ScoringProfile::default(),
+      score_format: ScoreFormat::default(),
+      normalize: false,
+      suggestions: false,
+    }
+  }
+
+  /// Sets the profile used to validate judgements and compute scores.
+  ///
+  /// Judgements outside the limits of the profile are treated as invalid
+  /// codes, and scores rejected by its policy are replaced by
+  /// `<rejected score: reason>`.
+  pub fn with_profile(mut self, profile: ScoringProfile) -> CsvProcessor {
+    self.profile = profile;
+    self
+  }
+
+  /// Sets how scores are printed.
+  pub fn with_score_format(mut self, score_format: ScoreFormat) -> CsvProcessor {
+    self.score_format = score_format;
+    self
+  }
+
+  /// Sets whether valid codes are rewritten in their canonical form (see
+  /// [`StyleCode::normalize`]). Invalid codes are left as they are.
+  pub fn with_normalize(mut self, normalize: bool) -> CsvProcessor {
+    self.normalize = normalize;
+    self
+  }
+
+  /// Sets whether a `Suggestions` column is appended, listing for invalid
+  /// codes the valid codes they may have been meant to be (see [`suggest`]),
+  /// separated by spaces. The column is empty for valid codes.
+  pub fn with_suggestions(mut self, suggestions: bool) -> CsvProcessor {
+    self.suggestions = suggestions;
+    self
+  }
 
+  /// Processes one record, appending the decoded values of its code.
+  ///
+  /// Invalid codes do not fail the record: the score is replaced by
+  /// `<invalid code: reason>` and the other values by `<invalid code>`. A
+  /// record without the code column is treated as an invalid code.
+  pub fn process_record(&mut self, record: &csv::StringRecord) -> csv::StringRecord {
     if self.column_index.is_none() {
-      self.column_index = Option::from(record.len() - 1);
+      self.column_index = Option::from(record.len().saturating_sub(1));
     }
 
     let mut out = record.clone();
@@ -108,18 +236,39 @@ impl CsvProcessor {
       out.push_field("DIF");
       out.push_field("SOG");
       out.push_field("PEN");
+      if self.suggestions {
+        out.push_field("Suggestions");
+      }
 
       self.has_headers = false;
-      return Ok(out);
+      return out;
     }
 
     let ix = self.column_index.unwrap();
 
-    let code = &record[ix];
+    let code = record.get(ix);
 
-    match StyleCode::decode(code.trim()) {
-      Some(decoded) => {
-        out.push_field(&decoded.score().to_string());
+    match decode_field(record, ix, &self.profile) {
+      Ok((code, decoded)) => {
+        if self.normalize {
+          // The code was decoded, so it can be normalized.
+          let canonical = StyleCode::normalize(code).unwrap();
+          out = out
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+              if i == ix {
+                canonical.clone()
+              } else {
+                field.to_string()
+              }
+            })
+            .collect();
+        }
+        match decoded.score_with(&self.profile) {
+          Ok(score) => out.push_field(&score.format(&self.score_format)),
+          Err(err) => out.push_field(&format!("<rejected score: {}>", err)),
+        }
         out.push_field(&decoded.bas.to_string());
         out.push_field(&decoded.mov.to_string());
         out.push_field(&decoded.din.to_string());
@@ -130,13 +279,24 @@ impl CsvProcessor {
         out.push_field(&decoded.sog.to_string());
         out.push_field(&decoded.pen.to_string());
       }
-      None => {
-        for _ in 0..10 {
+      Err(err) => {
+        out.push_field(&format!("<invalid code: {}>", err));
+        for _ in 0..9 {
           out.push_field("<invalid code>");
         }
       }
     }
 
-    return Ok(out);
+    if self.suggestions {
+      let suggestions: Vec<String> = code
+        .map(|code| suggest(code.trim(), 5))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|suggestion| suggestion.code)
+        .collect();
+      out.push_field(&suggestions.join(" "));
+    }
+
+    out
   }
 }
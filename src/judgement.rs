@@ -0,0 +1,36 @@
+//! Parsing of textual style judgements.
+
+use crate::error::JudgementError;
+use crate::style_code::{Category, StyleCode};
+
+/// Parses a judgement string such as `bas=1,mov=3,gcc=2,pen=1`.
+///
+/// The string is a comma-separated list of `category=points` pairs, without
+/// spaces; category names are case insensitive and categories that are not
+/// mentioned are zero. The ranges of the categories are not checked: use
+/// [`StyleCode::validate`] or [`StyleCode::try_encode`] for that.
+pub fn parse_judgement(string: &str) -> Result<StyleCode, JudgementError> {
+  let mut out = StyleCode::default();
+  for key_val in string.split(',') {
+    let split: Vec<&str> = key_val.split('=').collect();
+    if split.len() != 2 {
+      return Err(JudgementError::MalformedEntry(key_val.to_string()));
+    }
+
+    let category = match Category::from_name(split[0]) {
+      Some(category) => category,
+      None => return Err(JudgementError::UnknownCategory(split[0].to_string())),
+    };
+    match split[1].parse::<u32>() {
+      Ok(value) => *out.get_mut(category) = value,
+      Err(_) => {
+        return Err(JudgementError::InvalidValue {
+          category,
+          value: split[1].to_string(),
+        })
+      }
+    }
+  }
+
+  Ok(out)
+}
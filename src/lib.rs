@@ -0,0 +1,55 @@
+//! Encoding, decoding and scoring of INCOM Style Codes.
+//!
+//! A [`StyleCode`] holds a style judgement. It can be decoded from its compact
+//! textual form with [`StyleCode::decode`], encoded back with
+//! [`StyleCode::encode`] and scored with [`StyleCode::score`]. Judgement
+//! strings such as `bas=1,mov=3` are read with [`parse_judgement`], and whole
+//! `csv` files of codes are decoded with [`process_file`]. The weights and
+//! limits used for scoring are defined by a [`ScoringProfile`].
+//!
+//! The `servizio-cli` binary is a thin command-line front end for this
+//! library.
+
+#![warn(missing_docs)]
+
+pub mod agreement;
+pub mod analysis;
+pub mod check;
+pub mod diff;
+pub mod error;
+pub mod explain;
+pub mod file_processing;
+pub mod judgement;
+pub mod output;
+pub mod panel;
+pub mod profile;
+pub mod score;
+pub mod search;
+pub mod style_code;
+pub mod suggest;
+pub mod table;
+pub mod target;
+
+pub use agreement::{measure_agreement, Agreement, AgreementReport, JudgeBias};
+pub use analysis::{analyze_code, CodeAnalysis};
+pub use check::{check_character, with_check, CHECK_SEPARATOR};
+pub use diff::{compare, CategoryDiff, Comparison};
+pub use error::{
+  AgreementError, CodeError, DecodeError, DiffError, EncodeError, JudgementError, PanelError,
+  ParseScoreError, ProcessError, ProfileError, ScoreError, SearchError,
+};
+pub use explain::{explain_decode, explain_encode, explain_encode_with_layout, Explanation};
+pub use file_processing::{process_file, read_panels, CsvProcessor, PanelFile};
+pub use judgement::parse_judgement;
+pub use output::{OutputFormat, Record, RecordWriter};
+pub use panel::{aggregate, AggregationMethod};
+pub use profile::ScoringProfile;
+pub use score::{Score, ScoreFormat, ScorePolicy};
+pub use search::{Constraint, Field, Search};
+pub use style_code::{Category, Layout, StyleCode};
+pub use suggest::{suggest, Suggestion, Typo};
+pub use table::{table, TableFilter};
+pub use target::{reach_target, CategoryChange, TargetPlan};
+
+#[cfg(test)]
+mod tests;
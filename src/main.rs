@@ -1,11 +1,10 @@
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg};
 
-mod style_code;
-use style_code::StyleCode;
-mod file_processing;
-use file_processing::process_file;
-
-mod tests;
+mod cli;
+use cli::{
+    adjust, agreement, analyze, decode, diff, encode, form, normalize, panel, process, repl,
+    search, table, target, LegacyMode, Settings,
+};
 
 static NAME: &str = "servizio-cli by AnFive";
 static VERSION: &str = "1.0.0";
@@ -15,64 +14,103 @@ fn create_clap_app<'a>() -> App<'a, 'a> {
     App::new(NAME)
         .version(VERSION)
         .about(ABOUT)
-        .arg(Arg::with_name("code").help("The Style Code to decode."))
+        .bin_name("servizio-cli")
+        .setting(AppSettings::VersionlessSubcommands)
+        .subcommand(decode::subcommand())
+        .subcommand(encode::subcommand())
+        .subcommand(process::subcommand())
+        .subcommand(normalize::subcommand())
+        .subcommand(analyze::subcommand())
+        .subcommand(table::subcommand())
+        .subcommand(search::subcommand())
+        .subcommand(target::subcommand())
+        .subcommand(adjust::subcommand())
+        .subcommand(diff::subcommand())
+        .subcommand(panel::subcommand())
+        .subcommand(agreement::subcommand())
+        .subcommand(repl::subcommand())
+        .subcommand(form::subcommand())
         .arg(
-            Arg::with_name("encode")
-                .help("String containing Style judgement to encode")
-                .long("encode")
-                .short("e")
-                .takes_value(true)
-                .conflicts_with("code"),
+            Arg::with_name("raw")
+                .help("Output raw data to standard output (useful for scripts)")
+                .long("raw")
+                .short("r")
+                .global(true),
         )
         .arg(
-            Arg::with_name("value")
-                .help("The requested value")
-                .long("value")
-                .short("v")
+            Arg::with_name("profile")
+                .help("The scoring profile: the name of a built-in profile (incom) or the path of a TOML or JSON profile file. Default is incom")
+                .long("profile")
                 .takes_value(true)
-                .requires("code"),
+                .global(true),
         )
         .arg(
-            Arg::with_name("raw")
-                .help("Output raw data to standard output (useful for scripts)")
-                .long("raw")
-                .short("r"),
+            Arg::with_name("score-policy")
+                .help("What to do with scores below the floor: raise them to the floor (clamp), keep them (allow) or report an error (reject). Default is set by the profile (clamp)")
+                .long("score-policy")
+                .takes_value(true)
+                .possible_values(&["clamp", "allow", "reject"])
+                .global(true),
         )
         .arg(
-            Arg::with_name("infile")
-                .help("(File processing mode) input file to process ")
-                .long("infile")
-                .requires("outfile")
+            Arg::with_name("score-floor")
+                .help("The lowest acceptable score, used by the clamp and reject policies. Default is set by the profile (0)")
+                .long("score-floor")
                 .takes_value(true)
-                .conflicts_with("encode"),
+                .allow_hyphen_values(true)
+                .global(true),
         )
         .arg(
-            Arg::with_name("outfile")
-                .help("(File processing mode) output file to write ")
-                .long("outfile")
+            Arg::with_name("decimals")
+                .help("Number of decimals used to print scores. Default is 1")
+                .long("decimals")
                 .takes_value(true)
-                .requires("infile"),
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("decimal-comma")
+                .help("Print scores with a decimal comma instead of a decimal dot")
+                .long("decimal-comma")
+                .global(true),
         )
+        // Legacy flags, replaced by the subcommands but still accepted.
         .arg(
-            Arg::with_name("headers")
-                .help("(File processing mode) output file to write ")
-                .long("headers")
-                .requires("infile"),
+            Arg::with_name("code")
+                .help("(Deprecated, use the decode subcommand) The Style Code to decode.")
+                .hidden(true),
+        )
+        .arg(decode::value_arg().requires("code").hidden(true))
+        .arg(
+            Arg::with_name("judgement")
+                .help("(Deprecated, use the encode subcommand) String containing Style judgement to encode")
+                .long("encode")
+                .short("e")
+                .takes_value(true)
+                .conflicts_with("code")
+                .hidden(true),
         )
         .arg(
-            Arg::with_name("column")
-                .help("(File processing mode) specifies the index of the column to decode (zero-based). Default is the last colums, as determined by the first row.")
-                .long("column")
-                .min_values(0)
+            Arg::with_name("infile")
+                .help("(Deprecated, use the process subcommand) input file to process")
+                .long("infile")
+                .requires("outfile")
                 .takes_value(true)
-                .requires("infile"),
+                .conflicts_with_all(&["code", "judgement"])
+                .hidden(true),
         )
         .arg(
-            Arg::with_name("delimiter")
-                .help("(File processing mode) specifies the delimiter in the CSV files. Default is comma")
-                .long("delimiter")
+            Arg::with_name("outfile")
+                .help("(Deprecated, use the process subcommand) output file to write")
+                .long("outfile")
                 .takes_value(true)
-                .requires("infile"),
+                .requires("infile")
+                .hidden(true),
+        )
+        .args(
+            &process::options()
+                .into_iter()
+                .map(|arg| arg.requires("infile").hidden(true))
+                .collect::<Vec<_>>(),
         )
         .arg(
             Arg::with_name("version")
@@ -82,53 +120,9 @@ fn create_clap_app<'a>() -> App<'a, 'a> {
         )
 }
 
-fn extract_code(string: &str) -> Option<StyleCode> {
-    let mut out = StyleCode {
-        bas: 0,
-        mov: 0,
-        din: 0,
-        com: 0,
-        sapd: 0,
-        gcc: 0,
-        dif: 0,
-        sog: 0,
-        pen: 0,
-    };
-    for key_val in string.split(",") {
-        let split: Vec<&str> = key_val.split("=").collect();
-        if split.len() != 2 {
-            return None;
-        }
-
-        if let Ok(value) = split[1].parse::<u32>() {
-            let field: &mut u32 = match split[0].to_ascii_lowercase().as_ref() {
-                "bas" => &mut out.bas,
-                "mov" => &mut out.mov,
-                "din" => &mut out.din,
-                "com" => &mut out.com,
-                "sapd" => &mut out.sapd,
-                "gcc" => &mut out.gcc,
-                "dif" => &mut out.dif,
-                "sog" => &mut out.sog,
-                "pen" => &mut out.pen,
-                _ => return None,
-            };
-            *field = value;
-        } else {
-            return None;
-        }
-    }
-
-    if out.valid() {
-        Some(out)
-    } else {
-        None
-    }
-}
-
 fn main() {
-    let app = create_clap_app();
-    let matches = app.get_matches();
+    let mut app = create_clap_app();
+    let matches = app.clone().get_matches();
 
     if matches.is_present("version") {
         println!("{} {}", NAME, VERSION);
@@ -136,101 +130,51 @@ fn main() {
         std::process::exit(0);
     }
 
-    let raw = matches.is_present("raw");
-
-    if let Some(code) = matches.value_of("code") {
-        // Decode mode
-        if !raw {
-            println!("Decoding input code: {}", code);
-        }
-
-        match StyleCode::decode(code) {
-            None => {
-                if !raw {
-                    println!("Invalid style code.");
-                }
-                std::process::exit(1);
+    // Global options may be given before or after the subcommand.
+    let (name, sub_matches) = matches.subcommand();
+    let settings = match Settings::from_matches(sub_matches.unwrap_or(&matches)) {
+        Ok(settings) => settings,
+        Err((msg, err_code)) => {
+            if matches.is_present("raw") {
+                eprintln!("{}", msg);
+            } else {
+                println!("{}", msg);
             }
-            Some(style) => {
-                match matches.value_of("value") {
-                    Some(requested_value) => {
-                        let out = match requested_value.to_ascii_lowercase().as_ref() {
-                            "score" => style.score().to_string(),
-                            "bas" => style.bas.to_string(),
-                            "mov" => style.mov.to_string(),
-                            "din" => style.din.to_string(),
-                            "com" => style.com.to_string(),
-                            "sapd" => style.sapd.to_string(),
-                            "gcc" => style.gcc.to_string(),
-                            "dif" => style.dif.to_string(),
-                            "sog" => style.sog.to_string(),
-                            "pen" => style.pen.to_string(),
-                            _ => {
-                                if !raw {
-                                    println!("Invalid requested value: {}", requested_value);
-                                }
-                                std::process::exit(3);
-                            }
-                        };
-                        if raw {
-                            println!("{}", out);
-                        } else {
-                            println!("{} : {}", requested_value.to_ascii_lowercase(), out);
-                        }
-                    }
-                    None => {
-                        if raw {
-                            println!("{}", style.raw_print());
-                        } else {
-                            println!("{}", style.pretty_print());
-                        }
-                    }
-                }
-                std::process::exit(0);
-            }
-        }
-    } else if let Some(encode_string) = matches.value_of("encode") {
-        // Encode mode
-        if !raw {
-            println!("Encode: {}", encode_string);
-        }
-
-        if let Some(encode_code) = extract_code(encode_string) {
-            println!("{}", encode_code.encode());
-            std::process::exit(0);
-        } else {
-            std::process::exit(2);
+            std::process::exit(err_code);
         }
-    } else if let Some(infile) = matches.value_of("infile") {
-        // File processing mode
-        let outfile = matches.value_of("outfile").unwrap();
-        let has_headers = matches.is_present("headers");
-        let column_index = matches
-            .value_of("column")
-            .and_then(|s| s.parse::<usize>().ok());
+    };
 
-        let delimiter = match matches.value_of("delimiter") {
-            Some(d) => {
-                let bytes = &d.as_bytes();
-                if bytes.len() != 1 {
-                    if !raw {
-                        println!("Invalid delimiter");
-                    }
-                    std::process::exit(4);
+    let exit_code = match (name, sub_matches) {
+        ("decode", Some(sub_matches)) => decode::run(sub_matches, &settings),
+        ("encode", Some(sub_matches)) => encode::run(sub_matches, &settings),
+        ("process", Some(sub_matches)) => process::run(sub_matches, &settings),
+        ("normalize", Some(sub_matches)) => normalize::run(sub_matches, &settings),
+        ("analyze", Some(sub_matches)) => analyze::run(sub_matches, &settings),
+        ("table", Some(sub_matches)) => table::run(sub_matches, &settings),
+        ("search", Some(sub_matches)) => search::run(sub_matches, &settings),
+        ("target", Some(sub_matches)) => target::run(sub_matches, &settings),
+        ("adjust", Some(sub_matches)) => adjust::run(sub_matches, &settings),
+        ("diff", Some(sub_matches)) => diff::run(sub_matches, &settings),
+        ("panel", Some(sub_matches)) => panel::run(sub_matches, &settings),
+        ("agreement", Some(sub_matches)) => agreement::run(sub_matches, &settings),
+        ("repl", Some(sub_matches)) => repl::run(sub_matches, &settings),
+        ("form", Some(sub_matches)) => form::run(sub_matches, &settings),
+        _ => match LegacyMode::from_matches(&matches) {
+            Some(mode) => {
+                eprintln!("{}", mode.warning());
+                match mode {
+                    LegacyMode::Decode => decode::run(&matches, &settings),
+                    LegacyMode::Encode => encode::run(&matches, &settings),
+                    LegacyMode::Process => process::run(&matches, &settings),
                 }
-                bytes[0] as char
             }
-            None => ',',
-        };
-
-        if let Err((msg, err_code)) =
-            process_file(infile, outfile, delimiter, has_headers, column_index)
-        {
-            println!("An error occurred: {}", msg);
-            std::process::exit(err_code);
-        } else {
-            println!("Processing completed.");
-            std::process::exit(0);
-        }
-    }
+            None => {
+                // No mode was selected.
+                app.print_help().unwrap();
+                println!();
+                1
+            }
+        },
+    };
+    std::process::exit(exit_code);
 }
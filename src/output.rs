@@ -0,0 +1,206 @@
+//! Machine-readable output of decoded judgements.
+//!
+//! Every format writes the same fields, in this order:
+//!
+//! | Field    | Type    | Description                                  |
+//! |----------|---------|----------------------------------------------|
+//! | `code`   | string  | The Style Code                               |
+//! | `layout` | integer | The number of letters of the code (1, 2, 3)  |
+//! | `score`  | number  | The score                                    |
+//! | `bas` .. `pen` | integer | The value of each category, in the order of [`Category::ALL`] |
+//!
+//! New fields may be added at the end in future versions, but existing fields
+//! are never renamed, removed or reordered.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+
+use crate::score::{Score, ScoreFormat};
+use crate::style_code::{Category, Layout, StyleCode};
+
+/// The output formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+  /// Human-readable text, as printed by [`StyleCode::pretty_print`].
+  Text,
+  /// One JSON object per record and per line (JSON Lines).
+  Json,
+  /// Comma-separated values, with a header row.
+  Csv,
+  /// Tab-separated values, with a header row.
+  Tsv,
+  /// One YAML document per record.
+  Yaml,
+}
+
+impl OutputFormat {
+  /// The names of the formats, as accepted by [`OutputFormat::from_str`].
+  pub const NAMES: [&'static str; 5] = ["text", "json", "csv", "tsv", "yaml"];
+}
+
+impl FromStr for OutputFormat {
+  type Err = String;
+
+  fn from_str(name: &str) -> Result<OutputFormat, String> {
+    match name.to_ascii_lowercase().as_ref() {
+      "text" => Ok(OutputFormat::Text),
+      "json" => Ok(OutputFormat::Json),
+      "csv" => Ok(OutputFormat::Csv),
+      "tsv" => Ok(OutputFormat::Tsv),
+      "yaml" => Ok(OutputFormat::Yaml),
+      _ => Err(format!("unknown output format '{}'", name)),
+    }
+  }
+}
+
+impl fmt::Display for OutputFormat {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let name = match self {
+      OutputFormat::Text => "text",
+      OutputFormat::Json => "json",
+      OutputFormat::Csv => "csv",
+      OutputFormat::Tsv => "tsv",
+      OutputFormat::Yaml => "yaml",
+    };
+    f.write_str(name)
+  }
+}
+
+/// A decoded (or encoded) Style Code, with its score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+  /// The Style Code.
+  pub code: String,
+  /// The layout of the code.
+  pub layout: Layout,
+  /// The judgement.
+  pub style: StyleCode,
+  /// The score of the judgement.
+  pub score: Score,
+}
+
+/// Writes [`Record`]s in one of the [`OutputFormat`]s.
+///
+/// Records are written as soon as they are received, so that output can be
+/// streamed.
+pub struct RecordWriter<W: Write> {
+  out: W,
+  format: OutputFormat,
+  score_format: ScoreFormat,
+  header_written: bool,
+}
+
+impl<W: Write> RecordWriter<W> {
+  /// Creates a writer. Scores are printed with `score_format`, except in the
+  /// JSON and YAML formats, where they are always numbers with a decimal dot.
+  pub fn new(out: W, format: OutputFormat, score_format: ScoreFormat) -> RecordWriter<W> {
+    RecordWriter {
+      out,
+      format,
+      score_format,
+      header_written: false,
+    }
+  }
+
+  /// The format of the writer.
+  pub fn format(&self) -> OutputFormat {
+    self.format
+  }
+
+  /// Writes one record.
+  pub fn write(&mut self, record: &Record) -> io::Result<()> {
+    let number_format = ScoreFormat {
+      decimal_comma: false,
+      ..self.score_format.clone()
+    };
+    match self.format {
+      OutputFormat::Text => {
+        let score = record.score.format(&self.score_format);
+        writeln!(self.out, "{}", record.style.pretty_print_score(&score))
+      }
+      OutputFormat::Json => {
+        serde_json::to_writer(&mut self.out, &Fields::new(record, &number_format))?;
+        writeln!(self.out)
+      }
+      OutputFormat::Csv | OutputFormat::Tsv => {
+        let delimiter = if self.format == OutputFormat::Csv {
+          b','
+        } else {
+          b'\t'
+        };
+        let mut writer = csv::WriterBuilder::new()
+          .delimiter(delimiter)
+          .from_writer(&mut self.out);
+        if !self.header_written {
+          let mut header = vec!["code", "layout", "score"];
+          header.extend(Category::ALL.iter().map(|c| c.name()));
+          writer.write_record(&header)?;
+          self.header_written = true;
+        }
+        let mut row = vec![
+          record.code.clone(),
+          record.layout.to_string(),
+          record.score.format(&self.score_format),
+        ];
+        row.extend(
+          Category::ALL
+            .iter()
+            .map(|&c| record.style.get(c).to_string()),
+        );
+        writer.write_record(&row)?;
+        writer.flush()
+      }
+      OutputFormat::Yaml => {
+        writeln!(self.out, "---")?;
+        serde_yaml::to_writer(&mut self.out, &Fields::new(record, &number_format))
+          .map_err(io::Error::other)
+      }
+    }
+  }
+
+  /// The underlying writer.
+  pub fn get_mut(&mut self) -> &mut W {
+    &mut self.out
+  }
+
+  /// Flushes the output and returns the underlying writer.
+  pub fn finish(mut self) -> io::Result<W> {
+    self.out.flush()?;
+    Ok(self.out)
+  }
+}
+
+/// The fields of a record in the JSON and YAML formats, with the score
+/// rounded to the requested number of decimals.
+struct Fields<'a> {
+  record: &'a Record,
+  score: f64,
+}
+
+impl<'a> Fields<'a> {
+  fn new(record: &'a Record, number_format: &ScoreFormat) -> Fields<'a> {
+    Fields {
+      record,
+      // The formatted score is always a valid number with a decimal dot.
+      score: record.score.format(number_format).parse().unwrap(),
+    }
+  }
+}
+
+impl Serialize for Fields<'_> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let record = self.record;
+    let mut fields = serializer.serialize_struct("Record", 3 + Category::ALL.len())?;
+    fields.serialize_field("code", &record.code)?;
+    fields.serialize_field("layout", &record.layout)?;
+    fields.serialize_field("score", &self.score)?;
+    for &category in Category::ALL.iter() {
+      fields.serialize_field(category.name(), &record.style.get(category))?;
+    }
+    fields.end()
+  }
+}
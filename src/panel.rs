@@ -0,0 +1,100 @@
+//! Aggregation of the judgements of a panel of judges into a final judgement.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::PanelError;
+use crate::style_code::{Category, StyleCode};
+
+/// How the values given by the judges to a category are combined.
+///
+/// Every method rounds to the nearest integer, halves up, so the final value
+/// of a category is always between the lowest and the highest value given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationMethod {
+  /// The mean of the values.
+  Mean,
+  /// The median of the values; with an even number of judges, the mean of
+  /// the two middle values.
+  Median,
+  /// The mean of the values without the highest and the lowest one. Needs at
+  /// least three judges.
+  TrimmedMean,
+}
+
+impl AggregationMethod {
+  /// The names of the methods, as accepted by
+  /// [`AggregationMethod::from_str`].
+  pub const NAMES: [&'static str; 3] = ["mean", "median", "trimmed"];
+
+  /// The smallest number of judges the method needs.
+  pub fn minimum_judges(self) -> usize {
+    match self {
+      AggregationMethod::Mean | AggregationMethod::Median => 1,
+      AggregationMethod::TrimmedMean => 3,
+    }
+  }
+
+  /// Combines the values of one category, sorted in ascending order.
+  fn combine(self, sorted: &[u32]) -> u32 {
+    let values = match self {
+      AggregationMethod::Mean => sorted,
+      // The middle value, or the two middle values.
+      AggregationMethod::Median => &sorted[(sorted.len() - 1) / 2..=sorted.len() / 2],
+      AggregationMethod::TrimmedMean => &sorted[1..sorted.len() - 1],
+    };
+    let sum: u64 = values.iter().map(|&v| u64::from(v)).sum();
+    let count = values.len() as u64;
+    // The mean, rounded half up.
+    ((2 * sum + count) / (2 * count)) as u32
+  }
+}
+
+impl FromStr for AggregationMethod {
+  type Err = String;
+
+  fn from_str(name: &str) -> Result<AggregationMethod, String> {
+    match name.to_ascii_lowercase().as_ref() {
+      "mean" => Ok(AggregationMethod::Mean),
+      "median" => Ok(AggregationMethod::Median),
+      "trimmed" => Ok(AggregationMethod::TrimmedMean),
+      _ => Err(format!("unknown aggregation method '{}'", name)),
+    }
+  }
+}
+
+impl fmt::Display for AggregationMethod {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let name = match self {
+      AggregationMethod::Mean => "mean",
+      AggregationMethod::Median => "median",
+      AggregationMethod::TrimmedMean => "trimmed",
+    };
+    f.write_str(name)
+  }
+}
+
+/// Aggregates the judgements of a panel into a final judgement, category by
+/// category, with `method`.
+///
+/// The final judgement is valid if the judgements are, and is within the
+/// limits of any profile that they are within.
+pub fn aggregate(
+  judgements: &[StyleCode],
+  method: AggregationMethod,
+) -> Result<StyleCode, PanelError> {
+  if judgements.len() < method.minimum_judges() {
+    return Err(PanelError::TooFewJudges {
+      method,
+      judges: judgements.len(),
+      minimum: method.minimum_judges(),
+    });
+  }
+  let mut result = StyleCode::default();
+  for &category in Category::ALL.iter() {
+    let mut values: Vec<u32> = judgements.iter().map(|j| j.get(category)).collect();
+    values.sort_unstable();
+    *result.get_mut(category) = method.combine(&values);
+  }
+  Ok(result)
+}
@@ -0,0 +1,258 @@
+//! Scoring profiles: the weights, base and limits used to score judgements.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::{CodeError, EncodeError, ProfileError};
+use crate::score::{Score, ScorePolicy};
+use crate::style_code::{Category, Layout, StyleCode};
+
+/// The weights, base score and category limits used to score judgements.
+///
+/// The profile only affects scoring and validation: the encoding of Style
+/// Codes is the same for every profile, so limits can be lower than, but
+/// never exceed, what a code can represent (see [`Category::max`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoringProfile {
+  name: String,
+  base: Score,
+  weights: [Score; 9],
+  limits: [u32; 9],
+  score_policy: ScorePolicy,
+}
+
+impl Default for ScoringProfile {
+  /// The standard INCOM profile: a base of 5.5, 0.2 per style point, +0.1
+  /// per SOG point and -0.5 per penalty, with the limits of the encoding.
+  fn default() -> ScoringProfile {
+    ScoringProfile {
+      name: "incom".to_string(),
+      base: Score::from_tenths(55),
+      weights: [
+        Score::from_tenths(2),
+        Score::from_tenths(2),
+        Score::from_tenths(2),
+        Score::from_tenths(2),
+        Score::from_tenths(2),
+        Score::from_tenths(2),
+        Score::from_tenths(2),
+        Score::from_tenths(1),
+        Score::from_tenths(-5),
+      ],
+      limits: [3, 3, 3, 3, 3, 3, 3, 3, StyleCode::MAX_PENALTIES],
+      score_policy: ScorePolicy::default(),
+    }
+  }
+}
+
+/// A profile as written in a TOML or JSON file. Missing values are taken from
+/// the default profile.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+  name: Option<String>,
+  base: Option<f64>,
+  #[serde(default)]
+  weights: HashMap<String, f64>,
+  #[serde(default)]
+  limits: HashMap<String, u32>,
+  score_policy: Option<String>,
+  score_floor: Option<f64>,
+}
+
+impl ScoringProfile {
+  /// The names of the built-in profiles.
+  pub const BUILTIN: [&'static str; 1] = ["incom"];
+
+  /// Returns the built-in profile called `name`, if any.
+  pub fn builtin(name: &str) -> Option<ScoringProfile> {
+    match name.to_ascii_lowercase().as_ref() {
+      "incom" => Some(ScoringProfile::default()),
+      _ => None,
+    }
+  }
+
+  /// Selects a profile by name: a built-in profile if one is called `name`,
+  /// otherwise the profile file at that path.
+  pub fn select(name: &str) -> Result<ScoringProfile, ProfileError> {
+    match ScoringProfile::builtin(name) {
+      Some(profile) => Ok(profile),
+      None => ScoringProfile::load(name),
+    }
+  }
+
+  /// Loads a profile from a file. Files with a `.json` extension are read as
+  /// JSON, any other file as TOML.
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<ScoringProfile, ProfileError> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+      .map_err(|err| ProfileError::Io(format!("{}: {}", path.display(), err)))?;
+    let is_json = path
+      .extension()
+      .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+      ScoringProfile::from_json_str(&content)
+    } else {
+      ScoringProfile::from_toml_str(&content)
+    }
+  }
+
+  /// Parses a profile written in TOML.
+  pub fn from_toml_str(content: &str) -> Result<ScoringProfile, ProfileError> {
+    let file: ProfileFile =
+      toml::from_str(content).map_err(|err| ProfileError::Parse(err.to_string()))?;
+    ScoringProfile::from_file(file)
+  }
+
+  /// Parses a profile written in JSON.
+  pub fn from_json_str(content: &str) -> Result<ScoringProfile, ProfileError> {
+    let file: ProfileFile =
+      serde_json::from_str(content).map_err(|err| ProfileError::Parse(err.to_string()))?;
+    ScoringProfile::from_file(file)
+  }
+
+  fn from_file(file: ProfileFile) -> Result<ScoringProfile, ProfileError> {
+    let mut profile = ScoringProfile::default();
+    if let Some(name) = file.name {
+      profile.name = name;
+    }
+    if let Some(base) = file.base {
+      profile.base = to_score("base", base)?;
+    }
+    for (key, &weight) in file.weights.iter() {
+      let category = parse_category(key)?;
+      profile.weights[index(category)] = to_score(key, weight)?;
+    }
+    for (key, &limit) in file.limits.iter() {
+      let category = parse_category(key)?;
+      if limit > category.max() {
+        return Err(ProfileError::LimitTooHigh {
+          category,
+          limit,
+          max: category.max(),
+        });
+      }
+      profile.limits[index(category)] = limit;
+    }
+
+    let floor = match file.score_floor {
+      Some(floor) => Some(to_score("score_floor", floor)?),
+      None => None,
+    };
+    profile.score_policy = match file.score_policy.as_deref() {
+      Some("clamp") => ScorePolicy::Clamp(floor.unwrap_or(Score::ZERO)),
+      Some("allow") => ScorePolicy::AllowNegative,
+      Some("reject") => ScorePolicy::Reject(floor.unwrap_or(Score::ZERO)),
+      Some(other) => {
+        return Err(ProfileError::InvalidValue(
+          "score_policy".to_string(),
+          other.to_string(),
+        ))
+      }
+      None => ScorePolicy::Clamp(floor.unwrap_or(Score::ZERO)),
+    };
+
+    Ok(profile)
+  }
+
+  /// The name of the profile.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// The score of a judgement with no points at all.
+  pub fn base(&self) -> Score {
+    self.base
+  }
+
+  /// The score added for each point in `category` (negative for penalties).
+  pub fn weight(&self, category: Category) -> Score {
+    self.weights[index(category)]
+  }
+
+  /// The largest value allowed in `category`.
+  pub fn limit(&self, category: Category) -> u32 {
+    self.limits[index(category)]
+  }
+
+  /// The policy applied to scores below the floor.
+  pub fn score_policy(&self) -> ScorePolicy {
+    self.score_policy
+  }
+
+  /// Sets the policy applied to scores below the floor.
+  pub fn with_score_policy(mut self, score_policy: ScorePolicy) -> ScoringProfile {
+    self.score_policy = score_policy;
+    self
+  }
+
+  /// Checks that every category of `style` is within the limits of the
+  /// profile, returning an error for the first one that is not.
+  pub fn validate(&self, style: &StyleCode) -> Result<(), EncodeError> {
+    for &category in Category::ALL.iter() {
+      let value = style.get(category);
+      if value > self.limit(category) {
+        return Err(EncodeError::OutOfRange {
+          category,
+          value,
+          max: self.limit(category),
+        });
+      }
+    }
+    Ok(())
+  }
+
+  /// Decodes `code`, like [`StyleCode::decode`], and checks that the
+  /// judgement is within the limits of the profile.
+  pub fn decode(&self, code: &str) -> Result<StyleCode, CodeError> {
+    self.decode_with_layout(code).map(|(style, _)| style)
+  }
+
+  /// Decodes `code`, like [`StyleCode::decode_with_layout`], and checks that
+  /// the judgement is within the limits of the profile.
+  pub fn decode_with_layout(&self, code: &str) -> Result<(StyleCode, Layout), CodeError> {
+    let (style, layout) = StyleCode::decode_with_layout(code)?;
+    self.validate(&style)?;
+    Ok((style, layout))
+  }
+
+  /// Encodes `style` in the shortest layout, like [`StyleCode::try_encode`],
+  /// after checking that it is within the limits of the profile.
+  pub fn encode(&self, style: &StyleCode) -> Result<String, EncodeError> {
+    self.validate(style)?;
+    style.try_encode()
+  }
+
+  /// Encodes `style` in `layout`, like [`StyleCode::try_encode_with_layout`],
+  /// after checking that it is within the limits of the profile.
+  pub fn encode_with_layout(
+    &self,
+    style: &StyleCode,
+    layout: Layout,
+  ) -> Result<String, EncodeError> {
+    self.validate(style)?;
+    style.try_encode_with_layout(layout)
+  }
+}
+
+fn index(category: Category) -> usize {
+  Category::ALL.iter().position(|&c| c == category).unwrap()
+}
+
+fn parse_category(key: &str) -> Result<Category, ProfileError> {
+  Category::from_name(key).ok_or_else(|| ProfileError::UnknownCategory(key.to_string()))
+}
+
+/// Converts a decimal number from a profile file to an exact score.
+fn to_score(key: &str, value: f64) -> Result<Score, ProfileError> {
+  let tenths = (value * 10.0).round();
+  if (tenths - value * 10.0).abs() > 1e-6 || tenths.abs() > f64::from(i32::MAX) {
+    return Err(ProfileError::InvalidValue(
+      key.to_string(),
+      value.to_string(),
+    ));
+  }
+  Ok(Score::from_tenths(tenths as i32))
+}
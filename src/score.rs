@@ -0,0 +1,241 @@
+//! Exact scores and policies for computing them.
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+use crate::error::{ParseScoreError, ScoreError};
+
+/// A score, stored exactly as an integer number of tenths of a point.
+///
+/// Scores are printed with one decimal by default; a different number of
+/// decimals can be requested with the formatting precision (e.g. `{:.2}`) or
+/// with [`Score::format`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Score {
+  tenths: i32,
+}
+
+impl Score {
+  /// A score of zero.
+  pub const ZERO: Score = Score { tenths: 0 };
+
+  /// Creates a score from a number of tenths of a point.
+  pub const fn from_tenths(tenths: i32) -> Score {
+    Score { tenths }
+  }
+
+  /// The score in tenths of a point.
+  pub const fn tenths(self) -> i32 {
+    self.tenths
+  }
+
+  /// Adds two scores, or returns `None` if the result cannot be represented.
+  pub fn checked_add(self, other: Score) -> Option<Score> {
+    self
+      .tenths
+      .checked_add(other.tenths)
+      .map(Score::from_tenths)
+  }
+
+  /// Multiplies the score, or returns `None` if the result cannot be
+  /// represented.
+  pub fn checked_mul(self, factor: i32) -> Option<Score> {
+    self.tenths.checked_mul(factor).map(Score::from_tenths)
+  }
+
+  /// The score as a floating-point number, for further (inexact) processing.
+  pub fn as_f64(self) -> f64 {
+    f64::from(self.tenths) / 10.0
+  }
+
+  /// Formats the score according to `format`.
+  pub fn format(self, format: &ScoreFormat) -> String {
+    let out = self.format_decimals(format.decimals);
+    if format.decimal_comma {
+      out.replace('.', ",")
+    } else {
+      out
+    }
+  }
+
+  fn format_decimals(self, decimals: usize) -> String {
+    let sign = if self.tenths < 0 { "-" } else { "" };
+    let abs = self.tenths.unsigned_abs();
+    if decimals == 0 {
+      // Round half away from zero.
+      let units = (abs + 5) / 10;
+      let sign = if units == 0 { "" } else { sign };
+      format!("{}{}", sign, units)
+    } else {
+      format!(
+        "{}{}.{}{}",
+        sign,
+        abs / 10,
+        abs % 10,
+        "0".repeat(decimals - 1)
+      )
+    }
+  }
+}
+
+impl fmt::Display for Score {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(&self.format_decimals(f.precision().unwrap_or(1)))
+  }
+}
+
+impl Serialize for Score {
+  /// Serializes the score as a number, e.g. `7.2`.
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(self.as_f64())
+  }
+}
+
+impl FromStr for Score {
+  type Err = ParseScoreError;
+
+  /// Parses a score such as `7.2`, `-1.5`, `6` or `7,2`.
+  ///
+  /// Further decimals are accepted only if they are zero, since scores are
+  /// exact to the tenth.
+  fn from_str(string: &str) -> Result<Score, ParseScoreError> {
+    let err = || ParseScoreError(string.to_string());
+    let (negative, digits) = match string.strip_prefix('-') {
+      Some(rest) => (true, rest),
+      None => (false, string),
+    };
+    let (integer, decimals) = match digits.find(['.', ',']) {
+      Some(i) => (&digits[..i], &digits[i + 1..]),
+      None => (digits, ""),
+    };
+    if integer.is_empty()
+      || !integer
+        .bytes()
+        .chain(decimals.bytes())
+        .all(|b| b.is_ascii_digit())
+      || decimals.bytes().skip(1).any(|b| b != b'0')
+    {
+      return Err(err());
+    }
+    let units: i32 = integer.parse().map_err(|_| err())?;
+    let tenth = decimals.bytes().next().map_or(0, |b| i32::from(b - b'0'));
+    let tenths = units
+      .checked_mul(10)
+      .and_then(|t| t.checked_add(tenth))
+      .ok_or_else(err)?;
+    Ok(Score::from_tenths(if negative { -tenths } else { tenths }))
+  }
+}
+
+impl Add for Score {
+  type Output = Score;
+
+  fn add(self, other: Score) -> Score {
+    Score::from_tenths(self.tenths + other.tenths)
+  }
+}
+
+impl AddAssign for Score {
+  fn add_assign(&mut self, other: Score) {
+    self.tenths += other.tenths;
+  }
+}
+
+impl Sub for Score {
+  type Output = Score;
+
+  fn sub(self, other: Score) -> Score {
+    Score::from_tenths(self.tenths - other.tenths)
+  }
+}
+
+impl SubAssign for Score {
+  fn sub_assign(&mut self, other: Score) {
+    self.tenths -= other.tenths;
+  }
+}
+
+impl Neg for Score {
+  type Output = Score;
+
+  fn neg(self) -> Score {
+    Score::from_tenths(-self.tenths)
+  }
+}
+
+impl Mul<i32> for Score {
+  type Output = Score;
+
+  fn mul(self, factor: i32) -> Score {
+    Score::from_tenths(self.tenths * factor)
+  }
+}
+
+impl Sum for Score {
+  fn sum<I: Iterator<Item = Score>>(iter: I) -> Score {
+    iter.fold(Score::ZERO, Add::add)
+  }
+}
+
+impl<'a> Sum<&'a Score> for Score {
+  fn sum<I: Iterator<Item = &'a Score>>(iter: I) -> Score {
+    iter.copied().sum()
+  }
+}
+
+/// How scores are printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreFormat {
+  /// Number of decimals; scores are rounded half away from zero when this is
+  /// zero, and padded with zeros when it is more than one.
+  pub decimals: usize,
+  /// Use a comma instead of a dot as decimal separator (e.g. for Italian
+  /// locales).
+  pub decimal_comma: bool,
+}
+
+impl Default for ScoreFormat {
+  /// One decimal, with a dot as decimal separator.
+  fn default() -> ScoreFormat {
+    ScoreFormat {
+      decimals: 1,
+      decimal_comma: false,
+    }
+  }
+}
+
+/// What happens to scores that fall below a floor.
+///
+/// Penalties can bring the score of a valid judgement below zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScorePolicy {
+  /// Scores below the floor are raised to the floor.
+  Clamp(Score),
+  /// Scores are not bounded and may be negative.
+  AllowNegative,
+  /// Scores below the floor are an error.
+  Reject(Score),
+}
+
+impl Default for ScorePolicy {
+  /// Scores below zero are raised to zero.
+  fn default() -> ScorePolicy {
+    ScorePolicy::Clamp(Score::ZERO)
+  }
+}
+
+impl ScorePolicy {
+  /// Applies the policy to a score.
+  pub fn apply(self, score: Score) -> Result<Score, ScoreError> {
+    match self {
+      ScorePolicy::Clamp(floor) => Ok(score.max(floor)),
+      ScorePolicy::AllowNegative => Ok(score),
+      ScorePolicy::Reject(floor) if score < floor => Err(ScoreError::BelowFloor { score, floor }),
+      ScorePolicy::Reject(_) => Ok(score),
+    }
+  }
+}
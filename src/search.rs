@@ -0,0 +1,299 @@
+//! Reverse search: the judgements that match a set of constraints, such as a
+//! score or a code pattern.
+
+use std::cmp::{Ordering, Reverse};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::SearchError;
+use crate::output::Record;
+use crate::profile::ScoringProfile;
+use crate::score::Score;
+use crate::style_code::{Category, Layout};
+use crate::table::{table, TableFilter};
+
+/// A field of a [`Record`] that can be constrained or sorted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+  /// The code.
+  Code,
+  /// The number of letters of the code.
+  Layout,
+  /// The score.
+  Score,
+  /// The value of a category.
+  Category(Category),
+}
+
+impl Field {
+  /// Looks up a field by its name (case insensitive): `code`, `layout`,
+  /// `score` or the name of a category.
+  pub fn from_name(name: &str) -> Option<Field> {
+    match name.to_ascii_lowercase().as_ref() {
+      "code" => Some(Field::Code),
+      "layout" => Some(Field::Layout),
+      "score" => Some(Field::Score),
+      name => Category::from_name(name).map(Field::Category),
+    }
+  }
+
+  /// Compares two records on this field.
+  fn compare(self, a: &Record, b: &Record) -> Ordering {
+    match self {
+      Field::Code => a.code.cmp(&b.code),
+      _ => self.number(a).cmp(&self.number(b)),
+    }
+  }
+
+  /// The value of a numeric field, in tenths for the score.
+  fn number(self, record: &Record) -> i64 {
+    match self {
+      Field::Code => 0,
+      Field::Layout => record.layout.letters() as i64,
+      Field::Score => i64::from(record.score.tenths()),
+      Field::Category(category) => i64::from(record.style.get(category)),
+    }
+  }
+}
+
+impl fmt::Display for Field {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Field::Code => f.write_str("code"),
+      Field::Layout => f.write_str("layout"),
+      Field::Score => f.write_str("score"),
+      Field::Category(category) => f.write_str(category.name()),
+    }
+  }
+}
+
+/// The comparison operators and their symbols.
+const OPERATORS: [(&str, Operator); 6] = [
+  ("!=", Operator::Ne),
+  ("<=", Operator::Le),
+  (">=", Operator::Ge),
+  ("=", Operator::Eq),
+  ("<", Operator::Lt),
+  (">", Operator::Gt),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+}
+
+impl Operator {
+  fn holds(self, ordering: Ordering) -> bool {
+    match self {
+      Operator::Eq => ordering == Ordering::Equal,
+      Operator::Ne => ordering != Ordering::Equal,
+      Operator::Lt => ordering == Ordering::Less,
+      Operator::Le => ordering != Ordering::Greater,
+      Operator::Gt => ordering == Ordering::Greater,
+      Operator::Ge => ordering != Ordering::Less,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+  /// A number, in tenths for the score.
+  Number(i64),
+  /// A lowercase code pattern, where `*` matches any characters and `?` any
+  /// one character.
+  Pattern(Vec<char>),
+}
+
+/// A constraint on the judgements found by a [`Search`], such as `score=7.5`,
+/// `pen=0`, `mov>=2` or `code=g1*`.
+///
+/// Numeric fields can be compared with `=`, `!=`, `<`, `<=`, `>` and `>=`.
+/// The code can only be compared with `=` and `!=`, to a pattern where `*`
+/// matches any characters and `?` any one character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+  field: Field,
+  operator: Operator,
+  value: Value,
+}
+
+impl FromStr for Constraint {
+  type Err = SearchError;
+
+  fn from_str(string: &str) -> Result<Constraint, SearchError> {
+    // The first operator in the string, and the longest one at that position,
+    // so that `<=` is not read as `<`.
+    let (start, symbol, operator) = OPERATORS
+      .iter()
+      .filter_map(|&(symbol, operator)| string.find(symbol).map(|i| (i, symbol, operator)))
+      .min_by_key(|&(i, symbol, _)| (i, Reverse(symbol.len())))
+      .ok_or_else(|| SearchError::MalformedConstraint(string.to_string()))?;
+    let name = string[..start].trim();
+    let text = string[start + symbol.len()..].trim();
+    if name.is_empty() || text.is_empty() {
+      return Err(SearchError::MalformedConstraint(string.to_string()));
+    }
+
+    let field =
+      Field::from_name(name).ok_or_else(|| SearchError::UnknownField(name.to_string()))?;
+    let invalid = || SearchError::InvalidValue {
+      field: field.to_string(),
+      value: text.to_string(),
+    };
+    let value = match field {
+      Field::Code => {
+        if operator != Operator::Eq && operator != Operator::Ne {
+          return Err(SearchError::InvalidOperator {
+            field: field.to_string(),
+            operator: symbol.to_string(),
+          });
+        }
+        if !text
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || c == '*' || c == '?' || c == '-')
+        {
+          return Err(invalid());
+        }
+        Value::Pattern(text.chars().map(|c| c.to_ascii_lowercase()).collect())
+      }
+      Field::Score => Value::Number(i64::from(
+        text.parse::<Score>().map_err(|_| invalid())?.tenths(),
+      )),
+      Field::Layout | Field::Category(_) => {
+        Value::Number(text.parse::<u32>().map_err(|_| invalid())?.into())
+      }
+    };
+    Ok(Constraint {
+      field,
+      operator,
+      value,
+    })
+  }
+}
+
+impl Constraint {
+  /// Whether `record` satisfies the constraint.
+  pub fn accepts(&self, record: &Record) -> bool {
+    match &self.value {
+      Value::Pattern(pattern) => {
+        let code: Vec<char> = record.code.chars().collect();
+        let matched = matches_pattern(pattern, &code);
+        matched == (self.operator == Operator::Eq)
+      }
+      Value::Number(value) => self.operator.holds(self.field.number(record).cmp(value)),
+    }
+  }
+
+  /// Narrows `filter` to the judgements that can satisfy the constraint, so
+  /// that the others are not encoded and scored.
+  fn narrow(&self, filter: TableFilter) -> TableFilter {
+    let value = match self.value {
+      Value::Number(value) => u32::try_from(value).unwrap_or(u32::MAX),
+      Value::Pattern(_) => return filter,
+    };
+    match self.field {
+      Field::Category(category) => {
+        let (min, max) = match self.operator {
+          Operator::Eq => (value, value),
+          Operator::Ne => return filter,
+          // `< 0` accepts nothing.
+          Operator::Lt => value.checked_sub(1).map_or((1, 0), |max| (0, max)),
+          Operator::Le => (0, value),
+          Operator::Gt => (value.saturating_add(1), u32::MAX),
+          Operator::Ge => (value, u32::MAX),
+        };
+        filter.with_range(category, min, max)
+      }
+      Field::Layout if self.operator == Operator::Eq => {
+        match Layout::from_letters(value as usize) {
+          Some(layout) => filter.with_layout(layout),
+          None => filter,
+        }
+      }
+      _ => filter,
+    }
+  }
+}
+
+/// Whether `code` matches `pattern`, where `*` matches any characters and `?`
+/// any one character.
+fn matches_pattern(pattern: &[char], code: &[char]) -> bool {
+  match pattern.split_first() {
+    None => code.is_empty(),
+    Some(('*', rest)) => (0..=code.len()).any(|skip| matches_pattern(rest, &code[skip..])),
+    Some((&p, rest)) => match code.split_first() {
+      Some((&c, code)) => (p == '?' || p == c) && matches_pattern(rest, code),
+      None => false,
+    },
+  }
+}
+
+/// A reverse search: lists the judgements, within the limits of a scoring
+/// profile, that satisfy every constraint.
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+  constraints: Vec<Constraint>,
+  sort: Option<(Field, bool)>,
+  limit: Option<usize>,
+}
+
+impl Search {
+  /// Creates a search for the judgements that satisfy every constraint.
+  pub fn new(constraints: Vec<Constraint>) -> Search {
+    Search {
+      constraints,
+      ..Search::default()
+    }
+  }
+
+  /// Sorts the results on `field`, in descending order if `descending` is
+  /// true. Results with the same value are listed in the order of
+  /// [`StyleCode::all_valid`](crate::StyleCode::all_valid), which is also the
+  /// order of unsorted results.
+  pub fn with_sort(mut self, field: Field, descending: bool) -> Search {
+    self.sort = Some((field, descending));
+    self
+  }
+
+  /// Returns at most `limit` results (after sorting).
+  pub fn with_limit(mut self, limit: usize) -> Search {
+    self.limit = Some(limit);
+    self
+  }
+
+  /// Runs the search with the scores of `profile`. Judgements whose score is
+  /// rejected by the score policy of the profile are left out.
+  pub fn run(&self, profile: &ScoringProfile) -> Vec<Record> {
+    let filter = self
+      .constraints
+      .iter()
+      .fold(TableFilter::default(), |filter, constraint| {
+        constraint.narrow(filter)
+      });
+    let records =
+      table(profile, &filter).filter(|record| self.constraints.iter().all(|c| c.accepts(record)));
+    let limit = self.limit.unwrap_or(usize::MAX);
+    match self.sort {
+      None => records.take(limit).collect(),
+      Some((field, descending)) => {
+        let mut records: Vec<Record> = records.collect();
+        records.sort_by(|a, b| {
+          let ordering = field.compare(a, b);
+          if descending {
+            ordering.reverse()
+          } else {
+            ordering
+          }
+        });
+        records.truncate(limit);
+        records
+      }
+    }
+  }
+}
@@ -1,25 +1,174 @@
-use lazy_static::lazy_static;
-use regex::{Match, Regex};
-use std::convert::TryInto;
+//! The [`StyleCode`] judgement and its textual encoding.
 
-#[derive(Debug, PartialEq, Eq)]
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+use crate::check::{self, CHECK_SEPARATOR};
+use crate::error::{DecodeError, EncodeError, ScoreError};
+use crate::profile::ScoringProfile;
+use crate::score::{Score, ScoreFormat};
+
+/// A style judgement, i.e. the points awarded in each category.
+///
+/// A judgement is converted to and from its compact textual form (the
+/// Style Code) with [`StyleCode::encode`] and [`StyleCode::decode`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct StyleCode {
+  /// Base points (BAS).
   pub bas: u32,
+  /// Movement (MOV).
   pub mov: u32,
+  /// Dynamics (DIN).
   pub din: u32,
+  /// Composition (COM).
   pub com: u32,
+  /// SAPD.
   pub sapd: u32,
+  /// GCC.
   pub gcc: u32,
+  /// Difficulty (DIF).
   pub dif: u32,
+  /// Bonus points (SOG).
   pub sog: u32,
+  /// Number of penalties (PEN).
   pub pen: u32,
 }
 
-static ALPHABET_26: &str = "abcdefghijklmnopqrstuvwxyz";
-static ALPHABET_23: &str = "abcdefghjklmnpqrstuvwxy";
-static PENALTIES_CODE: &str = "0123456789abcdefghjkl";
+/// One of the categories of a style judgement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+  /// Base points (BAS).
+  Bas,
+  /// Movement (MOV).
+  Mov,
+  /// Dynamics (DIN).
+  Din,
+  /// Composition (COM).
+  Com,
+  /// SAPD.
+  Sapd,
+  /// GCC.
+  Gcc,
+  /// Difficulty (DIF).
+  Dif,
+  /// Bonus points (SOG).
+  Sog,
+  /// Number of penalties (PEN).
+  Pen,
+}
+
+impl Category {
+  /// All the categories, in the order they are printed.
+  pub const ALL: [Category; 9] = [
+    Category::Bas,
+    Category::Mov,
+    Category::Din,
+    Category::Com,
+    Category::Sapd,
+    Category::Gcc,
+    Category::Dif,
+    Category::Sog,
+    Category::Pen,
+  ];
+
+  /// The lowercase name of the category, as used in judgement strings.
+  pub fn name(self) -> &'static str {
+    match self {
+      Category::Bas => "bas",
+      Category::Mov => "mov",
+      Category::Din => "din",
+      Category::Com => "com",
+      Category::Sapd => "sapd",
+      Category::Gcc => "gcc",
+      Category::Dif => "dif",
+      Category::Sog => "sog",
+      Category::Pen => "pen",
+    }
+  }
+
+  /// Looks up a category by its name (case insensitive).
+  pub fn from_name(name: &str) -> Option<Category> {
+    let name = name.to_ascii_lowercase();
+    Category::ALL.iter().copied().find(|c| c.name() == name)
+  }
+
+  /// The largest value allowed in the category.
+  pub fn max(self) -> u32 {
+    match self {
+      Category::Pen => StyleCode::MAX_PENALTIES,
+      _ => 3,
+    }
+  }
+}
+
+impl fmt::Display for Category {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(&self.name().to_ascii_uppercase())
+  }
+}
+
+impl Serialize for Category {
+  /// Serializes the category as its name, e.g. `bas`.
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(self.name())
+  }
+}
+
+/// The layout of a Style Code: the number of letters encoding the categories
+/// other than BAS, SOG and PEN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layout {
+  /// One letter: MOV, DIN and GCC up to 2, everything else zero.
+  One,
+  /// Two letters: COM, SAPD and DIF up to 1.
+  Two,
+  /// Three letters: any judgement.
+  Three,
+}
+
+impl Layout {
+  /// The layouts, from the shortest to the longest.
+  pub const ALL: [Layout; 3] = [Layout::One, Layout::Two, Layout::Three];
+
+  /// The layout with `letters` letters, if any.
+  pub fn from_letters(letters: usize) -> Option<Layout> {
+    Layout::ALL.get(letters.wrapping_sub(1)).copied()
+  }
+
+  /// The number of letters of the layout.
+  pub fn letters(self) -> usize {
+    match self {
+      Layout::One => 1,
+      Layout::Two => 2,
+      Layout::Three => 3,
+    }
+  }
+}
+
+impl fmt::Display for Layout {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.letters())
+  }
+}
+
+impl Serialize for Layout {
+  /// Serializes the layout as its number of letters.
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(self.letters() as u64)
+  }
+}
+
+/// The characters that can appear in a code, in the order of their values as
+/// check characters.
+pub(crate) static ALPHABET_36: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+pub(crate) static ALPHABET_26: &str = "abcdefghijklmnopqrstuvwxyz";
+pub(crate) static ALPHABET_23: &str = "abcdefghjklmnpqrstuvwxy";
+pub(crate) static PENALTIES_CODE: &str = "0123456789abcdefghjkl";
 
 impl StyleCode {
+  /// The maximum number of penalties that can be represented in a code.
   pub const MAX_PENALTIES: u32 = 20;
 
   fn encode26(value: u32) -> &'static str {
@@ -27,7 +176,7 @@ impl StyleCode {
     &ALPHABET_26[i..i + 1]
   }
 
-  fn decode26(value: &str) -> Option<u32> {
+  fn decode26(value: char) -> Option<u32> {
     ALPHABET_26
       .find(value)
       .map(|i: usize| i.try_into().unwrap())
@@ -38,7 +187,7 @@ impl StyleCode {
     &ALPHABET_23[i..i + 1]
   }
 
-  fn decode23(value: &str) -> Option<u32> {
+  fn decode23(value: char) -> Option<u32> {
     ALPHABET_23
       .find(value)
       .map(|i: usize| i.try_into().unwrap())
@@ -49,141 +198,299 @@ impl StyleCode {
     &PENALTIES_CODE[i..i + 1]
   }
 
-  fn decode_penalties(value: &str) -> Option<u32> {
+  fn decode_penalties(value: char) -> Option<u32> {
     PENALTIES_CODE
       .find(value)
       .map(|i: usize| i.try_into().unwrap())
   }
 
-  pub fn decode(code: &str) -> Option<StyleCode> {
-    lazy_static! {
-      static ref DECODE_REGEX : Regex = Regex::new(r"^(?P<first>[a-zA-Z])(?P<points>[0-9]{1,2})(?:(?P<second>[a-hj-np-zA-HJ-NP-Z])(?:(?P<third>[a-zA-Z])?(?:(?P<sog>[0-3])(?P<pen>[0-9a-l])?)?)?)?$").unwrap();
+  /// Every valid judgement, with BAS changing fastest and PEN slowest.
+  pub fn all_valid() -> impl Iterator<Item = StyleCode> {
+    let count: u32 = Category::ALL.iter().map(|c| c.max() + 1).product();
+    (0..count).map(|mut index| {
+      let mut style = StyleCode::default();
+      for &category in Category::ALL.iter() {
+        let radix = category.max() + 1;
+        *style.get_mut(category) = index % radix;
+        index /= radix;
+      }
+      style
+    })
+  }
+
+  /// Decodes a Style Code (case insensitive) into a judgement.
+  ///
+  /// Returns a [`DecodeError`] describing the first problem found if the code
+  /// is malformed or describes an invalid judgement.
+  pub fn decode(code: &str) -> Result<StyleCode, DecodeError> {
+    StyleCode::decode_with_layout(code).map(|(style, _)| style)
+  }
+
+  /// Decodes a Style Code like [`decode`](StyleCode::decode), also returning
+  /// the layout of the code.
+  ///
+  /// Codes with a check character (see [`check`](crate::check)) are
+  /// verified, and a [`DecodeError::CheckMismatch`] is returned if the check
+  /// fails; codes without one are decoded as they are.
+  pub fn decode_with_layout(code: &str) -> Result<(StyleCode, Layout), DecodeError> {
+    let chars: Vec<char> = code.chars().map(|c| c.to_ascii_lowercase()).collect();
+    StyleCode::decode_lowercase(&chars)
+  }
+
+  /// Decodes a lowercase code, with or without check character.
+  pub(crate) fn decode_lowercase(chars: &[char]) -> Result<(StyleCode, Layout), DecodeError> {
+    match chars.iter().position(|&c| c == CHECK_SEPARATOR) {
+      Some(separator) => {
+        let decoded = StyleCode::decode_chars(&chars[..separator])?;
+        check::verify(chars, separator)?;
+        Ok(decoded)
+      }
+      None => StyleCode::decode_chars(chars),
     }
+  }
 
-    let code_lowercase = code.to_ascii_lowercase();
-    let m = match DECODE_REGEX.captures(&code_lowercase) {
-      Option::Some(v) => v,
-      None => return None,
+  /// Decodes a lowercase code without check character.
+  fn decode_chars(chars: &[char]) -> Result<(StyleCode, Layout), DecodeError> {
+    let first = match chars.first() {
+      Some(&c) => c,
+      None => return Err(DecodeError::Empty),
     };
-
-    let mut out = StyleCode {
-      bas: 0,
-      mov: 0,
-      din: 0,
-      com: 0,
-      sapd: 0,
-      gcc: 0,
-      dif: 0,
-      sog: 0,
-      pen: 0,
+    let first_value = match StyleCode::decode26(first) {
+      Some(val) => val,
+      None => {
+        return Err(DecodeError::InvalidFirstLetter {
+          offset: 0,
+          found: first,
+        })
+      }
     };
 
-    let points: u32 = m.name("points").unwrap().as_str().parse().unwrap();
-
-    out.sog = m
-      .name("sog")
-      .map_or_else(|| 0, |s: Match| s.as_str().parse().unwrap());
+    // Total of style points: one or two digits.
+    let points_offset = 1;
+    let mut pos = points_offset;
+    let mut points = 0;
+    while pos < chars.len() && pos < points_offset + 2 {
+      match chars[pos].to_digit(10) {
+        Some(digit) => points = points * 10 + digit,
+        None => break,
+      }
+      pos += 1;
+    }
+    if pos == points_offset {
+      return Err(DecodeError::MissingPoints { offset: pos });
+    }
 
-    if let Some(val) = m.name("pen") {
-      if let Some(pen) = StyleCode::decode_penalties(val.as_str()) {
-        out.pen = pen;
-      } else {
-        return None;
+    // Optional second and third letters.
+    let mut second = None;
+    let mut third = None;
+    if let Some(&c) = chars.get(pos).filter(|c| c.is_ascii_alphabetic()) {
+      second = Some((pos, c));
+      pos += 1;
+      if let Some(&c) = chars.get(pos).filter(|c| c.is_ascii_alphabetic()) {
+        third = Some((pos, c));
+        pos += 1;
       }
     }
-    let first_str = m.name("first").unwrap().as_str();
-    let second_group = m.name("second");
-    let third_group = m.name("third");
 
-    match third_group {
-      None => {
-        match second_group {
-          Some(_) if second_group.unwrap().as_str() != "z" => {
-            // 2-letters code
-            let mut remainder;
-            let second_letter = second_group.unwrap().as_str();
-            if let Some(val1) = StyleCode::decode26(first_str) {
-              if let Some(val2) = StyleCode::decode23(second_letter) {
-                remainder = val1 * 23 + val2;
-              } else {
-                return None;
-              }
-            } else {
-              return None;
-            }
+    let mut out = StyleCode::default();
 
-            out.mov = remainder / (4 * 4 * 2 * 2 * 2);
-            remainder = remainder % (4 * 4 * 2 * 2 * 2);
-            out.din = remainder / (4 * 2 * 2 * 2);
-            remainder = remainder % (4 * 2 * 2 * 2);
-            out.gcc = remainder / (2 * 2 * 2);
-            remainder = remainder % (2 * 2 * 2);
-            out.com = remainder / (2 * 2);
-            remainder = remainder % (2 * 2);
-            out.sapd = remainder / 2;
-            out.dif = remainder % 2;
+    // Optional SOG digit, only allowed after a second letter (or the `z`
+    // separator), optionally followed by the penalties.
+    if second.is_some() {
+      if let Some(&c) = chars.get(pos).filter(|c| c.is_ascii_digit()) {
+        out.sog = match c.to_digit(4) {
+          Some(sog) => sog,
+          None => {
+            return Err(DecodeError::InvalidSog {
+              offset: pos,
+              found: c,
+            })
           }
-          None | Some(_) => {
-            // 1-letter code
-            let mut remainder;
-            if let Some(val) = StyleCode::decode26(first_str) {
-              remainder = val;
-            } else {
-              return None;
+        };
+        pos += 1;
+
+        if let Some(&c) = chars.get(pos).filter(|c| c.is_ascii_alphanumeric()) {
+          out.pen = match StyleCode::decode_penalties(c) {
+            Some(pen) => pen,
+            None => {
+              return Err(DecodeError::InvalidPenalty {
+                offset: pos,
+                found: c,
+              })
             }
-            out.mov = remainder / (3 * 3);
-            remainder = remainder % (3 * 3);
-            out.din = remainder / 3;
-            out.gcc = remainder % 3;
+          };
+          pos += 1;
+        }
+      }
+    }
+
+    if let Some(&c) = chars.get(pos) {
+      return Err(DecodeError::TrailingCharacters {
+        offset: pos,
+        found: c,
+      });
+    }
+
+    let layout = match (second, third) {
+      (None, _) | (Some((_, 'z')), None) => {
+        // 1-letter code
+        let mut remainder = first_value;
+        out.mov = remainder / (3 * 3);
+        remainder %= 3 * 3;
+        out.din = remainder / 3;
+        out.gcc = remainder % 3;
+        Layout::One
+      }
+      (Some((second_offset, second_letter)), None) => {
+        // 2-letters code
+        let val2 = match StyleCode::decode23(second_letter) {
+          Some(val) => val,
+          None => {
+            return Err(DecodeError::ExcludedLetter {
+              offset: second_offset,
+              found: second_letter,
+            })
           }
+        };
+        let mut remainder = first_value * 23 + val2;
+        let max = 4 * 4 * 4 * 2 * 2 * 2 - 1;
+        if remainder > max {
+          return Err(DecodeError::LetterValueOutOfRange {
+            offset: 0,
+            letters: 2,
+            value: remainder,
+            max,
+          });
         }
+
+        out.mov = remainder / (4 * 4 * 2 * 2 * 2);
+        remainder %= 4 * 4 * 2 * 2 * 2;
+        out.din = remainder / (4 * 2 * 2 * 2);
+        remainder %= 4 * 2 * 2 * 2;
+        out.gcc = remainder / (2 * 2 * 2);
+        remainder %= 2 * 2 * 2;
+        out.com = remainder / (2 * 2);
+        remainder %= 2 * 2;
+        out.sapd = remainder / 2;
+        out.dif = remainder % 2;
+        Layout::Two
       }
-      Some(_) => {
+      (Some((second_offset, second_letter)), Some((_, third_letter))) => {
         // 3-letters code
-        let mut remainder;
-        if let Some(val1) = StyleCode::decode26(first_str) {
-          if let Some(val2) = StyleCode::decode23(second_group.unwrap().as_str()) {
-            if let Some(val3) = StyleCode::decode26(third_group.unwrap().as_str()) {
-              remainder = val1 * 26 * 23 + val2 * 26 + val3;
-            } else {
-              return None;
-            }
-          } else {
-            return None;
+        let val2 = match StyleCode::decode23(second_letter) {
+          Some(val) => val,
+          None => {
+            return Err(DecodeError::ExcludedLetter {
+              offset: second_offset,
+              found: second_letter,
+            })
           }
-        } else {
-          return None;
+        };
+        // The third letter is always in ALPHABET_26.
+        let val3 = StyleCode::decode26(third_letter).unwrap();
+        let mut remainder = first_value * 26 * 23 + val2 * 26 + val3;
+        let max = 4 * 4 * 4 * 4 * 4 * 4 - 1;
+        if remainder > max {
+          return Err(DecodeError::LetterValueOutOfRange {
+            offset: 0,
+            letters: 3,
+            value: remainder,
+            max,
+          });
         }
+
         out.mov = remainder / (4 * 4 * 4 * 4 * 4);
-        remainder = remainder % (4 * 4 * 4 * 4 * 4);
+        remainder %= 4 * 4 * 4 * 4 * 4;
         out.din = remainder / (4 * 4 * 4 * 4);
-        remainder = remainder % (4 * 4 * 4 * 4);
+        remainder %= 4 * 4 * 4 * 4;
         out.gcc = remainder / (4 * 4 * 4);
-        remainder = remainder % (4 * 4 * 4);
+        remainder %= 4 * 4 * 4;
         out.com = remainder / (4 * 4);
-        remainder = remainder % (4 * 4);
+        remainder %= 4 * 4;
         out.sapd = remainder / 4;
         out.dif = remainder % 4;
+        Layout::Three
       }
-    }
+    };
 
     let total_other_scores = out.mov + out.din + out.com + out.sapd + out.gcc + out.dif;
-    out.bas = if points >= total_other_scores {
-      points - total_other_scores
-    } else {
-      return None;
-    };
+    if points < total_other_scores {
+      return Err(DecodeError::PointsBelowCategories {
+        offset: points_offset,
+        points,
+        categories: total_other_scores,
+      });
+    }
+    out.bas = points - total_other_scores;
+    if out.bas > 3 {
+      return Err(DecodeError::PointsAboveMaximum {
+        offset: points_offset,
+        points,
+        max: total_other_scores + 3,
+      });
+    }
 
-    if out.valid() {
-      Option::from(out)
+    Ok((out, layout))
+  }
+
+  /// Rewrites a Style Code in its canonical form: the code produced by
+  /// [`encode`](StyleCode::encode) for the same judgement, i.e. lowercase,
+  /// with the shortest layout and without a needless SOG/PEN suffix.
+  ///
+  /// Codes with a check character keep it, recomputed for the canonical
+  /// code.
+  ///
+  /// Returns the same error as [`decode`](StyleCode::decode) if the code is
+  /// invalid.
+  pub fn normalize(code: &str) -> Result<String, DecodeError> {
+    // Decoded judgements are always valid, so they can be encoded.
+    let canonical = StyleCode::decode(code)?.encode();
+    if code.contains(CHECK_SEPARATOR) {
+      Ok(check::with_check(&canonical).unwrap())
     } else {
-      Option::None
+      Ok(canonical)
     }
   }
 
-  #[allow(dead_code)]
+  /// Checks whether a Style Code is in canonical form (see
+  /// [`normalize`](StyleCode::normalize)).
+  pub fn is_canonical(code: &str) -> Result<bool, DecodeError> {
+    StyleCode::normalize(code).map(|canonical| canonical == code)
+  }
+
+  /// Encodes the judgement into the shortest possible Style Code.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the judgement is not [`valid`](StyleCode::valid); use
+  /// [`try_encode`](StyleCode::try_encode) to handle invalid judgements.
   pub fn encode(&self) -> String {
-    assert!(self.valid());
+    match self.try_encode() {
+      Ok(code) => code,
+      Err(err) => panic!("cannot encode an invalid judgement: {}", err),
+    }
+  }
+
+  /// Encodes the judgement into the shortest possible Style Code, or
+  /// returns an [`EncodeError`] if the judgement is not valid.
+  pub fn try_encode(&self) -> Result<String, EncodeError> {
+    self.try_encode_with_layout(self.layout())
+  }
+
+  /// Encodes the judgement into a Style Code with the given layout, which
+  /// may be longer than the shortest one (e.g. for fixed-width forms).
+  ///
+  /// Returns [`EncodeError::LayoutTooSmall`] if the layout cannot represent
+  /// the judgement. The code is decoded to the same judgement by
+  /// [`decode`](StyleCode::decode).
+  pub fn try_encode_with_layout(&self, layout: Layout) -> Result<String, EncodeError> {
+    self.validate()?;
+    if layout < self.layout() {
+      return Err(EncodeError::LayoutTooSmall {
+        layout,
+        minimum: self.layout(),
+      });
+    }
 
     // Compute total number of style points.
     let points = self.bas + self.mov + self.din + self.com + self.sapd + self.gcc + self.dif;
@@ -191,72 +498,178 @@ impl StyleCode {
     // Create string representation
     let mut out = String::with_capacity(8);
 
-    let one_letter;
+    match layout {
+      Layout::One => {
+        // 1-letter format
+        out.push_str(StyleCode::encode26(
+          (self.mov * 3 + self.din) * 3 + self.gcc,
+        ));
+        out.push_str(&points.to_string());
+      }
+      Layout::Two => {
+        // 2-letters format
+        let value = ((((self.mov * 4 + self.din) * 4 + self.gcc) * 2 + self.com) * 2 + self.sapd)
+          * 2
+          + self.dif;
+        out.push_str(StyleCode::encode26(value / 23));
+        out.push_str(&points.to_string());
+        out.push_str(StyleCode::encode23(value % 23));
+      }
+      Layout::Three => {
+        // 3-letters format
+        let value = ((((self.mov * 4 + self.din) * 4 + self.gcc) * 4 + self.com) * 4 + self.sapd)
+          * 4
+          + self.dif;
+        out.push_str(StyleCode::encode26(value / (23 * 26)));
+        out.push_str(&points.to_string());
+        let remainder = value % (23 * 26);
+        out.push_str(StyleCode::encode23(remainder / 26));
+        out.push_str(StyleCode::encode26(remainder % 26));
+      }
+    }
+
+    if self.sog + self.pen != 0 {
+      if layout == Layout::One {
+        out.push('z');
+      }
+
+      out.push_str(&self.sog.to_string());
+      if self.pen != 0 {
+        let penalties = std::cmp::min(self.pen, StyleCode::MAX_PENALTIES);
+        out.push_str(StyleCode::encode_penalties(penalties));
+      }
+    }
+
+    Ok(out)
+  }
+
+  /// The layout of the code produced by [`encode`](StyleCode::encode), i.e.
+  /// the shortest layout that can represent the judgement.
+  pub fn layout(&self) -> Layout {
     if (self.com + self.sapd + self.dif == 0)
       && (self.mov + self.din + self.gcc < 6)
       && self.mov < 3
       && self.din < 3
       && self.gcc < 3
     {
-      // 1-letter format
-      one_letter = true;
-      out.push_str(StyleCode::encode26(
-        (self.mov * 3 + self.din) * 3 + self.gcc,
-      ));
-      out.push_str(&points.to_string());
+      Layout::One
     } else if self.com < 2 && self.sapd < 2 && self.dif < 2 {
-      // 2-letters format
-      one_letter = false;
-      let value = ((((self.mov * 4 + self.din) * 4 + self.gcc) * 2 + self.com) * 2 + self.sapd) * 2
-        + self.dif;
-      out.push_str(StyleCode::encode26(value / 23));
-      out.push_str(&points.to_string());
-      out.push_str(StyleCode::encode23(value % 23));
+      Layout::Two
     } else {
-      // 3-letters format
-      one_letter = false;
-      let value = ((((self.mov * 4 + self.din) * 4 + self.gcc) * 4 + self.com) * 4 + self.sapd) * 4
-        + self.dif;
-      out.push_str(StyleCode::encode26(value / (23 * 26)));
-      out.push_str(&points.to_string());
-      let remainder = value % (23 * 26);
-      out.push_str(StyleCode::encode23(remainder / 26));
-      out.push_str(StyleCode::encode26(remainder % 26));
+      Layout::Three
     }
+  }
 
-    if self.sog + self.pen != 0 {
-      if one_letter {
-        out.push('z');
+  /// Returns `true` if every category is within its allowed range.
+  pub fn valid(&self) -> bool {
+    self.validate().is_ok()
+  }
+
+  /// Checks that every category is within its allowed range, returning an
+  /// error for the first one that is not.
+  pub fn validate(&self) -> Result<(), EncodeError> {
+    for &category in Category::ALL.iter() {
+      let value = self.get(category);
+      if value > category.max() {
+        return Err(EncodeError::OutOfRange {
+          category,
+          value,
+          max: category.max(),
+        });
       }
+    }
+    Ok(())
+  }
 
-      out.push_str(&self.sog.to_string());
-      if self.pen != 0 {
-        let penalties = std::cmp::min(self.pen, StyleCode::MAX_PENALTIES);
-        out.push_str(StyleCode::encode_penalties(penalties));
+  /// Returns the value of a category.
+  pub fn get(&self, category: Category) -> u32 {
+    match category {
+      Category::Bas => self.bas,
+      Category::Mov => self.mov,
+      Category::Din => self.din,
+      Category::Com => self.com,
+      Category::Sapd => self.sapd,
+      Category::Gcc => self.gcc,
+      Category::Dif => self.dif,
+      Category::Sog => self.sog,
+      Category::Pen => self.pen,
+    }
+  }
+
+  /// Returns a mutable reference to the value of a category.
+  pub fn get_mut(&mut self, category: Category) -> &mut u32 {
+    match category {
+      Category::Bas => &mut self.bas,
+      Category::Mov => &mut self.mov,
+      Category::Din => &mut self.din,
+      Category::Com => &mut self.com,
+      Category::Sapd => &mut self.sapd,
+      Category::Gcc => &mut self.gcc,
+      Category::Dif => &mut self.dif,
+      Category::Sog => &mut self.sog,
+      Category::Pen => &mut self.pen,
+    }
+  }
+
+  /// Returns the judgement with `delta` added to the value of each category,
+  /// e.g. `(Category::Pen, 1)` to add one penalty.
+  ///
+  /// Returns [`EncodeError::Negative`] or [`EncodeError::OutOfRange`] if the
+  /// adjusted judgement is not valid.
+  pub fn adjust(&self, deltas: &[(Category, i32)]) -> Result<StyleCode, EncodeError> {
+    let mut adjusted = *self;
+    for &(category, delta) in deltas {
+      let value = i64::from(adjusted.get(category)) + i64::from(delta);
+      if value < 0 {
+        return Err(EncodeError::Negative { category, value });
       }
+      // Values that do not fit are out of range anyway.
+      *adjusted.get_mut(category) = u32::try_from(value).unwrap_or(u32::MAX);
     }
+    adjusted.validate()?;
+    Ok(adjusted)
+  }
 
-    out
+  /// Computes the final score of the judgement with the default
+  /// [`ScoringProfile`], raising scores below zero to zero.
+  pub fn score(&self) -> Score {
+    self.score_with(&ScoringProfile::default()).unwrap()
   }
-  pub fn valid(&self) -> bool {
-    self.bas <= 3
-      && self.mov <= 3
-      && self.din <= 3
-      && self.com <= 3
-      && self.sapd <= 3
-      && self.gcc <= 3
-      && self.dif <= 3
-      && self.sog <= 3
-      && self.pen <= StyleCode::MAX_PENALTIES
-  }
-
-  pub fn score(&self) -> f32 {
-    let points = self.bas + self.mov + self.din + self.com + self.sapd + self.gcc + self.dif;
-    let score = 55 + 2 * points + 1 * self.sog - 5 * self.pen;
-    score as f32 / 10.0
+
+  /// Computes the final score of the judgement with the weights of
+  /// `profile`, applying its policy to scores below the floor. Fails if the
+  /// score is out of the range of [`Score`].
+  pub fn score_with(&self, profile: &ScoringProfile) -> Result<Score, ScoreError> {
+    let score = Category::ALL
+      .iter()
+      .try_fold(profile.base(), |score, &category| {
+        let value = i32::try_from(self.get(category)).ok()?;
+        score.checked_add(profile.weight(category).checked_mul(value)?)
+      })
+      .ok_or(ScoreError::Overflow)?;
+    profile.score_policy().apply(score)
   }
 
+  /// Formats the judgement as a human-readable list of values.
   pub fn pretty_print(&self) -> String {
+    self
+      .pretty_print_with(&ScoringProfile::default(), &ScoreFormat::default())
+      .unwrap()
+  }
+
+  /// Formats the judgement as a human-readable list of values, computing the
+  /// score with `profile` and printing it with `format`.
+  pub fn pretty_print_with(
+    &self,
+    profile: &ScoringProfile,
+    format: &ScoreFormat,
+  ) -> Result<String, ScoreError> {
+    Ok(self.pretty_print_score(&self.score_with(profile)?.format(format)))
+  }
+
+  /// Formats the judgement as a human-readable list of values, with an
+  /// already formatted score.
+  pub(crate) fn pretty_print_score(&self, score: &str) -> String {
     format!(
       "Score: {}
 BAS  : {}
@@ -269,7 +682,7 @@ DIF  : {}
 SOG  : {}
 PEN  : {}
 ",
-      self.score(),
+      score,
       self.bas,
       self.mov,
       self.din,
@@ -282,8 +695,21 @@ PEN  : {}
     )
   }
 
+  /// Formats the score and the categories, one bare value per line.
   pub fn raw_print(&self) -> String {
-    format!(
+    self
+      .raw_print_with(&ScoringProfile::default(), &ScoreFormat::default())
+      .unwrap()
+  }
+
+  /// Formats the score and the categories, one bare value per line,
+  /// computing the score with `profile` and printing it with `format`.
+  pub fn raw_print_with(
+    &self,
+    profile: &ScoringProfile,
+    format: &ScoreFormat,
+  ) -> Result<String, ScoreError> {
+    Ok(format!(
       "{}
 {}
 {}
@@ -295,7 +721,7 @@ PEN  : {}
 {}
 {}
 ",
-      self.score(),
+      self.score_with(profile)?.format(format),
       self.bas,
       self.mov,
       self.din,
@@ -305,6 +731,14 @@ PEN  : {}
       self.dif,
       self.sog,
       self.pen
-    )
+    ))
+  }
+}
+
+impl TryFrom<&StyleCode> for String {
+  type Error = EncodeError;
+
+  fn try_from(style: &StyleCode) -> Result<String, EncodeError> {
+    style.try_encode()
   }
 }
@@ -0,0 +1,164 @@
+//! Suggestions of valid codes for mistyped ones.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::style_code::{StyleCode, ALPHABET_36};
+
+/// Pairs of characters that are easily confused in handwriting.
+static LOOK_ALIKES: [(char, char); 14] = [
+  ('i', '1'),
+  ('i', 'l'),
+  ('l', '1'),
+  ('i', 'j'),
+  ('o', '0'),
+  ('o', 'a'),
+  ('z', '2'),
+  ('s', '5'),
+  ('b', '8'),
+  ('b', '6'),
+  ('g', '9'),
+  ('q', '9'),
+  ('t', '7'),
+  ('u', 'v'),
+];
+
+/// The typo that a suggestion corrects. The variants are ordered from the
+/// most to the least likely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Typo {
+  /// A character was confused with a similar-looking one, e.g. `i` and `1`.
+  LookAlike {
+    /// The offset of the character.
+    offset: usize,
+    /// The character in the code.
+    found: char,
+    /// The character in the suggestion.
+    replacement: char,
+  },
+  /// Two adjacent characters were swapped.
+  Transposition {
+    /// The offset of the first of the two characters.
+    offset: usize,
+  },
+  /// A character was replaced by another one.
+  Substitution {
+    /// The offset of the character.
+    offset: usize,
+    /// The character in the code.
+    found: char,
+    /// The character in the suggestion.
+    replacement: char,
+  },
+}
+
+impl fmt::Display for Typo {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Typo::LookAlike {
+        offset,
+        found,
+        replacement,
+      } => write!(
+        f,
+        "'{}' at offset {} read as '{}'",
+        found, offset, replacement
+      ),
+      Typo::Transposition { offset } => write!(
+        f,
+        "characters at offsets {} and {} swapped",
+        offset,
+        offset + 1
+      ),
+      Typo::Substitution {
+        offset,
+        found,
+        replacement,
+      } => write!(
+        f,
+        "'{}' at offset {} replaced by '{}'",
+        found, offset, replacement
+      ),
+    }
+  }
+}
+
+/// A valid code that an invalid one may have been meant to be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+  /// The suggested code.
+  pub code: String,
+  /// The typo that turns the suggested code into the invalid one.
+  pub typo: Typo,
+}
+
+/// Suggests up to `limit` valid codes that differ from `code` by a single
+/// typo: a look-alike character (such as `i`, `l` and `1`, or `o` and `0`),
+/// two swapped adjacent characters or any other single character.
+///
+/// Suggestions are ranked by likelihood: look-alikes first, then
+/// transpositions, then other substitutions, and by offset within each kind.
+/// Valid codes get no suggestions.
+pub fn suggest(code: &str, limit: usize) -> Vec<Suggestion> {
+  let chars: Vec<char> = code.chars().map(|c| c.to_ascii_lowercase()).collect();
+  if StyleCode::decode(code).is_ok() {
+    return Vec::new();
+  }
+
+  let mut candidates = Vec::new();
+  for (offset, &found) in chars.iter().enumerate() {
+    for &(a, b) in LOOK_ALIKES.iter() {
+      let replacement = match found {
+        c if c == a => b,
+        c if c == b => a,
+        _ => continue,
+      };
+      candidates.push(Typo::LookAlike {
+        offset,
+        found,
+        replacement,
+      });
+    }
+    for replacement in ALPHABET_36.chars().filter(|&c| c != found) {
+      candidates.push(Typo::Substitution {
+        offset,
+        found,
+        replacement,
+      });
+    }
+  }
+  for offset in 0..chars.len().saturating_sub(1) {
+    if chars[offset] != chars[offset + 1] {
+      candidates.push(Typo::Transposition { offset });
+    }
+  }
+  // Typos are ordered by kind, then by offset.
+  candidates.sort();
+
+  let mut seen = HashSet::new();
+  let mut suggestions = Vec::new();
+  for typo in candidates {
+    let mut fixed = chars.clone();
+    match typo {
+      Typo::LookAlike {
+        offset,
+        replacement,
+        ..
+      }
+      | Typo::Substitution {
+        offset,
+        replacement,
+        ..
+      } => fixed[offset] = replacement,
+      Typo::Transposition { offset } => fixed.swap(offset, offset + 1),
+    }
+    let fixed: String = fixed.into_iter().collect();
+    if StyleCode::decode(&fixed).is_ok() && seen.insert(fixed.clone()) {
+      suggestions.push(Suggestion { code: fixed, typo });
+      if suggestions.len() == limit {
+        break;
+      }
+    }
+  }
+  suggestions
+}
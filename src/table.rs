@@ -0,0 +1,102 @@
+//! The lookup table of every valid code.
+
+use crate::error::JudgementError;
+use crate::output::Record;
+use crate::profile::ScoringProfile;
+use crate::style_code::{Category, Layout, StyleCode};
+
+/// Restricts the judgements listed in the lookup table.
+///
+/// The default filter accepts every judgement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableFilter {
+  layouts: Vec<Layout>,
+  ranges: Vec<(Category, u32, u32)>,
+}
+
+impl TableFilter {
+  /// Parses a filter string such as `pen=0,bas=1-2,mov=2-`.
+  ///
+  /// The string is a comma-separated list of `category=value` or
+  /// `category=min-max` entries, where either bound of a range can be left
+  /// out. A judgement must satisfy every entry.
+  pub fn parse(string: &str) -> Result<TableFilter, JudgementError> {
+    let mut filter = TableFilter::default();
+    for entry in string.split(',') {
+      let (name, value) = match entry.split_once('=') {
+        Some(split) => split,
+        None => return Err(JudgementError::MalformedEntry(entry.to_string())),
+      };
+      let category = match Category::from_name(name) {
+        Some(category) => category,
+        None => return Err(JudgementError::UnknownCategory(name.to_string())),
+      };
+      let invalid = || JudgementError::InvalidValue {
+        category,
+        value: value.to_string(),
+      };
+      let bound = |bound: &str, default: u32| match bound {
+        "" => Ok(default),
+        bound => bound.parse::<u32>().map_err(|_| invalid()),
+      };
+      let (min, max) = match value.split_once('-') {
+        Some((min, max)) if !(min.is_empty() && max.is_empty()) => {
+          (bound(min, 0)?, bound(max, u32::MAX)?)
+        }
+        Some(_) => return Err(invalid()),
+        None => {
+          let value = value.parse::<u32>().map_err(|_| invalid())?;
+          (value, value)
+        }
+      };
+      filter = filter.with_range(category, min, max);
+    }
+    Ok(filter)
+  }
+
+  /// Accepts only the judgements with a value of `category` between `min`
+  /// and `max` (inclusive).
+  pub fn with_range(mut self, category: Category, min: u32, max: u32) -> TableFilter {
+    self.ranges.push((category, min, max));
+    self
+  }
+
+  /// Accepts only the judgements encoded with `layout`. Can be called more
+  /// than once to accept several layouts.
+  pub fn with_layout(mut self, layout: Layout) -> TableFilter {
+    self.layouts.push(layout);
+    self
+  }
+
+  /// Whether the filter accepts `style`.
+  pub fn accepts(&self, style: &StyleCode) -> bool {
+    self
+      .ranges
+      .iter()
+      .all(|&(category, min, max)| (min..=max).contains(&style.get(category)))
+      && (self.layouts.is_empty() || self.layouts.contains(&style.layout()))
+  }
+}
+
+/// Lists every judgement within the limits of `profile` that `filter`
+/// accepts, with its code and score, in the order of
+/// [`StyleCode::all_valid`].
+///
+/// Judgements whose score is rejected by the score policy of the profile are
+/// left out.
+pub fn table<'a>(
+  profile: &'a ScoringProfile,
+  filter: &'a TableFilter,
+) -> impl Iterator<Item = Record> + 'a {
+  StyleCode::all_valid()
+    .filter(move |style| profile.validate(style).is_ok() && filter.accepts(style))
+    .filter_map(move |style| {
+      let score = style.score_with(profile).ok()?;
+      Some(Record {
+        code: style.encode(),
+        layout: style.layout(),
+        style,
+        score,
+      })
+    })
+}
@@ -0,0 +1,126 @@
+//! The changes to a judgement that reach a target score.
+
+use std::fmt;
+
+use crate::profile::ScoringProfile;
+use crate::score::Score;
+use crate::style_code::{Category, StyleCode};
+
+/// The change of the value of one category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CategoryChange {
+  /// The category.
+  pub category: Category,
+  /// The current value.
+  pub from: u32,
+  /// The new value.
+  pub to: u32,
+}
+
+impl CategoryChange {
+  /// The number of points changed.
+  pub fn points(&self) -> u32 {
+    self.to.abs_diff(self.from)
+  }
+}
+
+impl fmt::Display for CategoryChange {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} {} -> {}", self.category, self.from, self.to)
+  }
+}
+
+/// A judgement that reaches a target score, and how it differs from the
+/// current one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetPlan {
+  /// The new judgement.
+  pub style: StyleCode,
+  /// The score of the new judgement.
+  pub score: Score,
+  /// The categories that change, in the order of [`Category::ALL`].
+  pub changes: Vec<CategoryChange>,
+}
+
+impl TargetPlan {
+  /// The total number of points changed.
+  pub fn points(&self) -> u32 {
+    self.changes.iter().map(CategoryChange::points).sum()
+  }
+}
+
+/// Lists the minimal changes to `style` that reach a score of at least
+/// `target` with `profile`, within the limits of the profile.
+///
+/// A change is minimal if undoing any of its points would fall short of the
+/// target, so only the categories that raise the score are increased and only
+/// those that lower it (the penalties) are decreased. The plans are sorted by
+/// the number of points changed, then by the number of categories changed,
+/// then by score, highest first.
+///
+/// If `style` already reaches the target, the only plan has no changes. If
+/// the target cannot be reached, there are no plans.
+pub fn reach_target(style: &StyleCode, target: Score, profile: &ScoringProfile) -> Vec<TargetPlan> {
+  let reaches = |style: &StyleCode| style.score_with(profile).is_ok_and(|score| score >= target);
+
+  // Every judgement that only moves categories in the direction that raises
+  // the score.
+  let mut candidates = vec![*style];
+  for &category in Category::ALL.iter() {
+    let current = style.get(category);
+    let weight = profile.weight(category);
+    let values = if weight > Score::ZERO {
+      current..=profile.limit(category).max(current)
+    } else if weight < Score::ZERO {
+      0..=current
+    } else {
+      current..=current
+    };
+    candidates = candidates
+      .iter()
+      .flat_map(|candidate| {
+        values.clone().map(move |value| {
+          let mut candidate = *candidate;
+          *candidate.get_mut(category) = value;
+          candidate
+        })
+      })
+      .collect();
+  }
+
+  let mut plans: Vec<TargetPlan> = candidates
+    .into_iter()
+    .filter(|candidate| reaches(candidate))
+    .filter(|candidate| {
+      // Undoing one point of any change must fall short of the target.
+      Category::ALL.iter().all(|&category| {
+        let (from, to) = (style.get(category), candidate.get(category));
+        if from == to {
+          return true;
+        }
+        let mut undone = *candidate;
+        *undone.get_mut(category) = if to > from { to - 1 } else { to + 1 };
+        !reaches(&undone)
+      })
+    })
+    .map(|candidate| TargetPlan {
+      style: candidate,
+      score: candidate.score_with(profile).unwrap(),
+      changes: Category::ALL
+        .iter()
+        .filter(|&&category| candidate.get(category) != style.get(category))
+        .map(|&category| CategoryChange {
+          category,
+          from: style.get(category),
+          to: candidate.get(category),
+        })
+        .collect(),
+    })
+    .collect();
+  plans.sort_by(|a, b| {
+    (a.points(), a.changes.len())
+      .cmp(&(b.points(), b.changes.len()))
+      .then(b.score.cmp(&a.score))
+  });
+  plans
+}
@@ -1,54 +1,1175 @@
-#[cfg(test)]
-mod tests {
+use std::convert::TryFrom;
 
-  use crate::style_code::StyleCode;
+use crate::agreement::measure_agreement;
+use crate::analysis::analyze_code;
+use crate::check::{check_character, with_check};
+use crate::diff::compare;
+use crate::error::{
+  AgreementError, CodeError, DecodeError, DiffError, EncodeError, JudgementError, PanelError,
+  ProcessError, ProfileError, ScoreError, SearchError,
+};
+use crate::explain::{explain_decode, explain_encode};
+use crate::file_processing::{process_file, read_panels, CsvProcessor};
+use crate::judgement::parse_judgement;
+use crate::output::{OutputFormat, Record, RecordWriter};
+use crate::panel::{aggregate, AggregationMethod};
+use crate::profile::ScoringProfile;
+use crate::score::{Score, ScoreFormat, ScorePolicy};
+use crate::search::{Constraint, Field, Search};
+use crate::style_code::{Category, Layout, StyleCode};
+use crate::suggest::{suggest, Suggestion, Typo};
+use crate::table::{table, TableFilter};
+use crate::target::{reach_target, CategoryChange};
 
-  #[test]
-  fn roundtrip() {
-    let mut scores = [0, 0, 0, 0, 0, 0, 0, 0, 0];
-    let max = [3, 3, 3, 3, 3, 3, 3, 3, StyleCode::MAX_PENALTIES];
+#[test]
+fn roundtrip() {
+  let mut scores = [0, 0, 0, 0, 0, 0, 0, 0, 0];
+  let max = [3, 3, 3, 3, 3, 3, 3, 3, StyleCode::MAX_PENALTIES];
 
-    let mut counter = 0;
-    loop {
-      let code = StyleCode {
-        bas: scores[0],
-        mov: scores[1],
-        din: scores[2],
-        com: scores[3],
-        sapd: scores[4],
-        gcc: scores[5],
-        dif: scores[6],
-        sog: scores[7],
-        pen: scores[8],
-      };
+  let mut counter = 0;
+  loop {
+    let code = StyleCode {
+      bas: scores[0],
+      mov: scores[1],
+      din: scores[2],
+      com: scores[3],
+      sapd: scores[4],
+      gcc: scores[5],
+      dif: scores[6],
+      sog: scores[7],
+      pen: scores[8],
+    };
 
-      let code_str = code.encode();
+    let code_str = code.encode();
 
-      let decoded = StyleCode::decode(&code_str);
-      if !decoded.is_some() {
-        panic!("Style score could not be decoded: {}", code_str);
+    match StyleCode::decode(&code_str) {
+      Ok(decoded) => assert_eq!(code, decoded),
+      Err(err) => panic!("Style score could not be decoded: {}: {}", code_str, err),
+    }
+    counter += 1;
+    let mut i = 0;
+    loop {
+      scores[i] += 1;
+      if scores[i] > max[i] {
+        scores[i] = 0;
+        i += 1;
+        if i == 9 {
+          println!("Roundtrip test: {} codes tested.", counter);
+          assert_eq!(
+            counter,
+            4 * 4 * 4 * 4 * 4 * 4 * 4 * 4 * (StyleCode::MAX_PENALTIES + 1)
+          );
+          return;
+        }
+      } else {
+        break;
       }
+    }
+  }
+}
+
+#[test]
+fn judgement_parsing() {
+  let parsed = parse_judgement("bas=1,MOV=3,gcc=2,pen=1").unwrap();
+  assert_eq!(
+    parsed,
+    StyleCode {
+      bas: 1,
+      mov: 3,
+      gcc: 2,
+      pen: 1,
+      ..StyleCode::default()
+    }
+  );
+  assert_eq!(parsed.encode(), "r6k01");
+
+  assert_eq!(
+    parse_judgement("bas=1,foo=2"),
+    Err(JudgementError::UnknownCategory("foo".to_string()))
+  );
+  assert_eq!(
+    parse_judgement("bas:1"),
+    Err(JudgementError::MalformedEntry("bas:1".to_string()))
+  );
+  assert_eq!(
+    parse_judgement("pen=-1"),
+    Err(JudgementError::InvalidValue {
+      category: Category::Pen,
+      value: "-1".to_string()
+    })
+  );
+}
+
+#[test]
+fn encode_errors() {
+  let style = parse_judgement("bas=4,mov=1").unwrap();
+  assert!(!style.valid());
+  assert_eq!(
+    style.try_encode(),
+    Err(EncodeError::OutOfRange {
+      category: Category::Bas,
+      value: 4,
+      max: 3
+    })
+  );
+
+  let style = StyleCode {
+    pen: StyleCode::MAX_PENALTIES + 1,
+    ..StyleCode::default()
+  };
+  assert_eq!(
+    String::try_from(&style),
+    Err(EncodeError::OutOfRange {
+      category: Category::Pen,
+      value: StyleCode::MAX_PENALTIES + 1,
+      max: StyleCode::MAX_PENALTIES
+    })
+  );
+  assert_eq!(
+    String::try_from(&StyleCode::default()),
+    Ok("a0".to_string())
+  );
+}
 
-      assert_eq!(code, decoded.unwrap());
-      counter += 1;
-      let mut i = 0;
-      loop {
-        scores[i] += 1;
-        if scores[i] > max[i] {
-          scores[i] = 0;
-          i += 1;
-          if i == 9 {
-            println!("Roundtrip test: {} codes tested.", counter);
-            assert_eq!(
-              counter,
-              4 * 4 * 4 * 4 * 4 * 4 * 4 * 4 * (StyleCode::MAX_PENALTIES + 1)
-            );
-            return;
-          }
+#[test]
+fn forced_layouts() {
+  for value in 0..4 * 4 * 4 * 4 * 4 * 4 {
+    for &(sog, pen) in [(0, 0), (1, 0), (0, 5), (3, 20)].iter() {
+      let style = StyleCode {
+        bas: 2,
+        mov: value / 1024,
+        din: value / 256 % 4,
+        com: value / 64 % 4,
+        sapd: value / 16 % 4,
+        gcc: value / 4 % 4,
+        dif: value % 4,
+        sog,
+        pen,
+      };
+      for &layout in Layout::ALL.iter() {
+        let encoded = style.try_encode_with_layout(layout);
+        if layout < style.layout() {
+          assert_eq!(
+            encoded,
+            Err(EncodeError::LayoutTooSmall {
+              layout,
+              minimum: style.layout()
+            })
+          );
         } else {
-          break;
+          let code = encoded.unwrap();
+          assert_eq!(StyleCode::decode_with_layout(&code), Ok((style, layout)));
         }
       }
     }
   }
+  assert_eq!(Layout::from_letters(2), Some(Layout::Two));
+  assert_eq!(Layout::from_letters(0), None);
+}
+
+#[test]
+fn normalization() {
+  assert_eq!(StyleCode::normalize("n4"), Ok("n4".to_string()));
+  assert_eq!(StyleCode::normalize("N4"), Ok("n4".to_string()));
+  assert_eq!(StyleCode::normalize("n4z0"), Ok("n4".to_string()));
+  assert_eq!(StyleCode::normalize("b2sk"), Ok("j2".to_string()));
+  assert_eq!(StyleCode::is_canonical("g13dm12"), Ok(true));
+  assert_eq!(StyleCode::is_canonical("G13DM12"), Ok(false));
+  assert_eq!(
+    StyleCode::is_canonical("g"),
+    Err(DecodeError::MissingPoints { offset: 1 })
+  );
+}
+
+#[test]
+fn decode_errors() {
+  assert_eq!(StyleCode::decode(""), Err(DecodeError::Empty));
+  assert_eq!(
+    StyleCode::decode("5g1"),
+    Err(DecodeError::InvalidFirstLetter {
+      offset: 0,
+      found: '5'
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("g"),
+    Err(DecodeError::MissingPoints { offset: 1 })
+  );
+  assert_eq!(
+    StyleCode::decode("g13om12"),
+    Err(DecodeError::ExcludedLetter {
+      offset: 3,
+      found: 'o'
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("z10y"),
+    Err(DecodeError::LetterValueOutOfRange {
+      offset: 0,
+      letters: 2,
+      value: 597,
+      max: 511
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("g13dm5"),
+    Err(DecodeError::InvalidSog {
+      offset: 5,
+      found: '5'
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("g13dm1i"),
+    Err(DecodeError::InvalidPenalty {
+      offset: 6,
+      found: 'i'
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("g3dm"),
+    Err(DecodeError::PointsBelowCategories {
+      offset: 1,
+      points: 3,
+      categories: 12
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("n9"),
+    Err(DecodeError::PointsAboveMaximum {
+      offset: 1,
+      points: 9,
+      max: 6
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("g13dm12x"),
+    Err(DecodeError::TrailingCharacters {
+      offset: 7,
+      found: 'x'
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("G13DM12").unwrap(),
+    StyleCode::decode("g13dm12").unwrap()
+  );
+}
+
+#[test]
+fn negative_scores() {
+  let style = StyleCode {
+    pen: StyleCode::MAX_PENALTIES,
+    ..StyleCode::default()
+  };
+  let profile = |policy| ScoringProfile::default().with_score_policy(policy);
+  assert_eq!(style.score(), Score::ZERO);
+  assert_eq!(
+    style.score_with(&profile(ScorePolicy::Clamp(Score::from_tenths(-20)))),
+    Ok(Score::from_tenths(-20))
+  );
+  assert_eq!(
+    style.score_with(&profile(ScorePolicy::AllowNegative)),
+    Ok(Score::from_tenths(-45))
+  );
+  assert_eq!(
+    style.score_with(&profile(ScorePolicy::Reject(Score::ZERO))),
+    Err(ScoreError::BelowFloor {
+      score: Score::from_tenths(-45),
+      floor: Score::ZERO
+    })
+  );
+  assert_eq!(
+    StyleCode::decode("g13dm12")
+      .unwrap()
+      .score_with(&profile(ScorePolicy::Reject(Score::ZERO))),
+    Ok(Score::from_tenths(72))
+  );
+}
+
+#[test]
+fn score_overflow() {
+  let profile =
+    ScoringProfile::from_toml_str("base = 200000000\n[weights]\nbas = 10000000").unwrap();
+  let style = |bas| StyleCode {
+    bas,
+    ..StyleCode::default()
+  };
+  assert_eq!(
+    style(1).score_with(&profile),
+    Ok(Score::from_tenths(2_100_000_000))
+  );
+  assert_eq!(style(3).score_with(&profile), Err(ScoreError::Overflow));
+  assert_eq!(
+    style(u32::MAX).score_with(&ScoringProfile::default()),
+    Err(ScoreError::Overflow)
+  );
+}
+
+#[test]
+fn score_formatting() {
+  let score = StyleCode::decode("g13dm12").unwrap().score();
+  assert_eq!(score.to_string(), "7.2");
+  assert_eq!(format!("{:.2}", score), "7.20");
+  let italian = ScoreFormat {
+    decimals: 1,
+    decimal_comma: true,
+  };
+  assert_eq!(score.format(&italian), "7,2");
+  let rounded = ScoreFormat {
+    decimals: 0,
+    decimal_comma: false,
+  };
+  assert_eq!(Score::from_tenths(65).format(&rounded), "7");
+  assert_eq!(Score::from_tenths(-45).format(&rounded), "-5");
+  assert_eq!(Score::from_tenths(-4).format(&rounded), "0");
+  assert_eq!(Score::from_tenths(-5).to_string(), "-0.5");
+  assert_eq!(Score::from_tenths(60).to_string(), "6.0");
+
+  assert_eq!("7.2".parse(), Ok(score));
+  assert_eq!("7,20".parse(), Ok(score));
+  assert_eq!("-1.5".parse(), Ok(Score::from_tenths(-15)));
+  assert_eq!("6".parse(), Ok(Score::from_tenths(60)));
+  assert!("7.25".parse::<Score>().is_err());
+  assert!(".5".parse::<Score>().is_err());
+
+  let scores = ["7.1", "7.1", "7.1"];
+  let total: Score = scores.iter().map(|s| s.parse::<Score>().unwrap()).sum();
+  assert_eq!(total.to_string(), "21.3");
+  assert_eq!(total - score * 3, Score::from_tenths(-3));
+}
+
+#[test]
+fn scoring_profiles() {
+  let style = StyleCode::decode("g13dm12").unwrap();
+
+  let toml = ScoringProfile::from_toml_str(
+    r#"
+      name = "double-dif"
+      base = 5.0
+      score_policy = "reject"
+      score_floor = 1.0
+
+      [weights]
+      dif = 0.4
+      pen = -1
+
+      [limits]
+      pen = 10
+    "#,
+  )
+  .unwrap();
+  assert_eq!(toml.name(), "double-dif");
+  // 5.0 + 0.2 * 11 + 0.4 * 2 + 0.1 * 1 - 1 * 2
+  assert_eq!(style.score_with(&toml), Ok(Score::from_tenths(61)));
+  assert_eq!(
+    toml.score_policy(),
+    ScorePolicy::Reject(Score::from_tenths(10))
+  );
+  assert_eq!(
+    toml.validate(&StyleCode {
+      pen: 11,
+      ..StyleCode::default()
+    }),
+    Err(EncodeError::OutOfRange {
+      category: Category::Pen,
+      value: 11,
+      max: 10
+    })
+  );
+
+  let json = ScoringProfile::from_json_str(r#"{ "limits": { "mov": 2 } }"#).unwrap();
+  assert_eq!(
+    style.score_with(&json),
+    style.score_with(&ScoringProfile::default())
+  );
+  assert!(json.validate(&style).is_err());
+  let mov = EncodeError::OutOfRange {
+    category: Category::Mov,
+    value: 3,
+    max: 2,
+  };
+  assert_eq!(json.decode("g13dm12"), Err(CodeError::Limit(mov.clone())));
+  assert_eq!(
+    json.decode("g"),
+    Err(CodeError::Decode(DecodeError::MissingPoints { offset: 1 }))
+  );
+  assert_eq!(
+    json.decode_with_layout("n4"),
+    Ok((StyleCode::decode("n4").unwrap(), Layout::One))
+  );
+  assert_eq!(json.encode(&style), Err(mov));
+  assert_eq!(
+    json.encode_with_layout(&StyleCode::default(), Layout::Two),
+    Ok("a0a".to_string())
+  );
+
+  assert_eq!(
+    ScoringProfile::from_json_str(r#"{ "limits": { "sog": 4 } }"#),
+    Err(ProfileError::LimitTooHigh {
+      category: Category::Sog,
+      limit: 4,
+      max: 3
+    })
+  );
+  assert_eq!(
+    ScoringProfile::from_toml_str("[weights]\nfoo = 1"),
+    Err(ProfileError::UnknownCategory("foo".to_string()))
+  );
+  assert_eq!(
+    ScoringProfile::from_toml_str("base = 5.55"),
+    Err(ProfileError::InvalidValue(
+      "base".to_string(),
+      "5.55".to_string()
+    ))
+  );
+  assert!(ScoringProfile::from_toml_str("bas = 1").is_err());
+  assert_eq!(
+    ScoringProfile::select("INCOM"),
+    Ok(ScoringProfile::default())
+  );
+}
+
+#[test]
+fn output_formats() {
+  let (style, layout) = StyleCode::decode_with_layout("g13dm12").unwrap();
+  assert_eq!(layout, Layout::Three);
+  assert_eq!(StyleCode::decode_with_layout("n4").unwrap().1, Layout::One);
+  assert_eq!(StyleCode::decode_with_layout("d6r").unwrap().1, Layout::Two);
+  assert_eq!(style.layout(), layout);
+
+  let record = Record {
+    code: "g13dm12".to_string(),
+    layout,
+    style,
+    score: style.score(),
+  };
+  let write = |format: OutputFormat, score_format: ScoreFormat| {
+    let mut writer = RecordWriter::new(Vec::new(), format, score_format);
+    writer.write(&record).unwrap();
+    writer.write(&record).unwrap();
+    String::from_utf8(writer.finish().unwrap()).unwrap()
+  };
+  let italian = ScoreFormat {
+    decimals: 1,
+    decimal_comma: true,
+  };
+
+  let json_line = r#"{"code":"g13dm12","layout":3,"score":7.2,"bas":1,"mov":3,"din":2,"com":1,"sapd":3,"gcc":1,"dif":2,"sog":1,"pen":2}"#;
+  assert_eq!(
+    write(OutputFormat::Json, italian.clone()),
+    format!("{}\n{}\n", json_line, json_line)
+  );
+  assert_eq!(
+    write(OutputFormat::Csv, italian),
+    "code,layout,score,bas,mov,din,com,sapd,gcc,dif,sog,pen\n\
+     g13dm12,3,\"7,2\",1,3,2,1,3,1,2,1,2\n\
+     g13dm12,3,\"7,2\",1,3,2,1,3,1,2,1,2\n"
+  );
+  assert!(write(OutputFormat::Tsv, ScoreFormat::default()).starts_with("code\tlayout\tscore\tbas"));
+  assert!(write(OutputFormat::Yaml, ScoreFormat::default())
+    .starts_with("---\ncode: g13dm12\nlayout: 3\nscore: 7.2\nbas: 1\n"));
+
+  // Codes read from files are escaped in every format.
+  let record = Record {
+    code: "a\"b\\c\nd: e".to_string(),
+    ..record
+  };
+  for &format in [OutputFormat::Json, OutputFormat::Yaml].iter() {
+    let mut writer = RecordWriter::new(Vec::new(), format, ScoreFormat::default());
+    writer.write(&record).unwrap();
+    let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let value: serde_json::Value = if format == OutputFormat::Json {
+      serde_json::from_str(&out).unwrap()
+    } else {
+      serde_yaml::from_str(&out).unwrap()
+    };
+    assert_eq!(value["code"], record.code.as_str(), "{}", format);
+    assert_eq!(value["pen"], 2, "{}", format);
+  }
+  assert_eq!(
+    write(OutputFormat::Text, ScoreFormat::default()),
+    format!("{}\n{}\n", style.pretty_print(), style.pretty_print())
+  );
+  assert_eq!("YAML".parse(), Ok(OutputFormat::Yaml));
+}
+
+#[test]
+fn decode_explanations() {
+  let steps = explain_decode("G13DM12").unwrap().steps;
+  assert_eq!(steps[0], "Code 'g13dm12': 3-letter layout.");
+  assert!(steps.contains(&"Combined value: 6 * 23 * 26 + 3 * 26 + 12 = 3678.".to_string()));
+  assert!(steps.contains(&"MOV = 3678 / 1024 (4*4*4*4*4) = 3, remainder 606.".to_string()));
+  assert!(steps.contains(&"DIF = 2 (last remainder).".to_string()));
+  assert!(steps.contains(
+    &"BAS = points total - (MOV + DIN + COM + SAPD + GCC + DIF) = 13 - 12 = 1.".to_string()
+  ));
+  assert_eq!(
+    steps.last().unwrap(),
+    "Penalty character '2' (offset 6): index 2 in 0123456789abcdefghjkl, PEN = 2."
+  );
+
+  let steps = explain_decode("f3z2").unwrap().steps;
+  assert!(steps.contains(
+    &"Separator 'z' (offset 2): a SOG/PEN suffix follows the 1-letter code.".to_string()
+  ));
+  assert_eq!(steps.last().unwrap(), "No penalty character: PEN = 0.");
+
+  assert_eq!(
+    explain_decode("g13om12"),
+    Err(DecodeError::ExcludedLetter {
+      offset: 3,
+      found: 'o'
+    })
+  );
+}
+
+#[test]
+fn encode_explanations() {
+  let style = parse_judgement("bas=1,mov=3,gcc=2,pen=1").unwrap();
+  let steps = explain_encode(&style).unwrap().steps;
+  assert!(steps.contains(&"1-letter layout not possible: MOV is 3 (must be below 3).".to_string()));
+  assert!(steps.contains(&"2-letter layout chosen: all its conditions hold.".to_string()));
+  assert!(steps.contains(
+    &"Second letter: 400 % 23 = 9, index 9 in ALPHABET_23 (abcdefghjklmnpqrstuvwxy) is 'k'."
+      .to_string()
+  ));
+  assert_eq!(steps.last().unwrap(), "Code: r6k01.");
+
+  let style = parse_judgement("mov=1,din=1,gcc=1,sog=2").unwrap();
+  let steps = explain_encode(&style).unwrap().steps;
+  assert!(steps[2].starts_with("1-letter layout chosen"));
+  assert!(steps
+    .iter()
+    .any(|step| step.starts_with("Separator 'z' appended")));
+  assert_eq!(steps.last().unwrap(), "Code: n3z2.");
+
+  let style = parse_judgement("bas=4").unwrap();
+  assert!(explain_encode(&style).is_err());
+}
+
+#[test]
+fn typo_suggestions() {
+  assert_eq!(
+    suggest("GI3DM12", 5),
+    vec![Suggestion {
+      code: "g13dm12".to_string(),
+      typo: Typo::LookAlike {
+        offset: 1,
+        found: 'i',
+        replacement: '1'
+      }
+    }]
+  );
+  assert_eq!(
+    suggest("4n", 5)[0],
+    Suggestion {
+      code: "n4".to_string(),
+      typo: Typo::Transposition { offset: 0 }
+    }
+  );
+
+  let suggestions = suggest("r6k0i", 3);
+  let codes: Vec<&str> = suggestions.iter().map(|s| s.code.as_str()).collect();
+  assert_eq!(codes, ["r6k01", "r6k0j", "r6k0l"]);
+  assert!(suggestions
+    .iter()
+    .all(|s| StyleCode::decode(&s.code).is_ok()));
+
+  assert!(suggest("n4", 5).is_empty());
+  assert!(suggest("", 5).is_empty());
+}
+
+#[test]
+fn check_characters() {
+  let alphabet = "0123456789abcdefghijklmnopqrstuvwxyz";
+  for &code in ["g13dm12", "n4", "r6k01", "a0z0l", "d6r"].iter() {
+    let check = check_character(code).unwrap();
+    let chars: Vec<char> = code.chars().collect();
+    // Every single substitution and adjacent swap changes the check.
+    for i in 0..chars.len() {
+      for c in alphabet.chars().filter(|&c| c != chars[i]) {
+        let mut typo = chars.clone();
+        typo[i] = c;
+        let typo: String = typo.into_iter().collect();
+        assert_ne!(check_character(&typo), Some(check), "{}", typo);
+      }
+      if i + 1 < chars.len() && chars[i] != chars[i + 1] {
+        let mut typo = chars.clone();
+        typo.swap(i, i + 1);
+        let typo: String = typo.into_iter().collect();
+        assert_ne!(check_character(&typo), Some(check), "{}", typo);
+      }
+    }
+
+    let checked = with_check(code).unwrap();
+    assert_eq!(StyleCode::decode(&checked), StyleCode::decode(code));
+    assert_eq!(
+      StyleCode::decode(&checked.to_ascii_uppercase()),
+      StyleCode::decode(code)
+    );
+  }
+
+  assert_eq!(check_character("g13dm12-"), None);
+  let style = StyleCode::decode("g13dm12").unwrap();
+  let checked = with_check("g13dm12").unwrap();
+  let expected = checked.chars().last().unwrap();
+  let wrong = if expected == '0' { '1' } else { '0' };
+  assert_eq!(
+    StyleCode::decode(&format!("g13dm12-{}", wrong)),
+    Err(DecodeError::CheckMismatch {
+      offset: 8,
+      found: wrong,
+      expected
+    })
+  );
+  // A mistyped code that is still valid is caught by the check.
+  assert!(StyleCode::decode(&checked.replacen('d', "e", 1)).is_err());
+  assert_eq!(
+    StyleCode::decode("g13dm12-"),
+    Err(DecodeError::MissingCheckCharacter { offset: 8 })
+  );
+  assert_eq!(
+    StyleCode::decode(&format!("{}x", checked)),
+    Err(DecodeError::TrailingCharacters {
+      offset: 9,
+      found: 'x'
+    })
+  );
+  assert_eq!(StyleCode::decode(&checked), Ok(style));
+}
+
+#[test]
+fn typo_analysis() {
+  assert_eq!(
+    StyleCode::all_valid().count(),
+    4 * 4 * 4 * 4 * 4 * 4 * 4 * 4 * (StyleCode::MAX_PENALTIES + 1) as usize
+  );
+  assert!(StyleCode::all_valid().all(|style| style.valid()));
+
+  let profile = ScoringProfile::default();
+  let analysis = analyze_code("n4", &profile);
+  assert_eq!(analysis.layout, Layout::One);
+  assert_eq!(analysis.score, Score::from_tenths(63));
+  // 35 substitutions for each character, and one swap.
+  assert_eq!(analysis.edits, 2 * 35 + 1);
+  // e.g. n3 (BAS 0) and n5 (BAS 2), each one 0.2 away.
+  assert!(analysis.valid_edits >= 2);
+  assert!(analysis.max_score_change >= Score::from_tenths(2));
+  assert!(analysis.mean_score_change() > 0.0);
+
+  let analysis = analyze_code("a0", &profile);
+  assert_eq!(
+    serde_json::to_string(&analysis).unwrap(),
+    "{\"code\":\"a0\",\"layout\":1,\"score\":5.5,\"edits\":71,\"valid_edits\":3,\
+     \"max_score_change\":0.6,\"total_score_change\":1.2}"
+  );
+  let mut writer = csv::Writer::from_writer(Vec::new());
+  writer.serialize(&analysis).unwrap();
+  assert_eq!(
+    String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+    "code,layout,score,edits,valid_edits,max_score_change,total_score_change\n\
+     a0,1,5.5,71,3,0.6,1.2\n"
+  );
+
+  // With a check character, only edits of the separator can go unnoticed.
+  let checked = with_check("g13dm12").unwrap();
+  let analysis = analyze_code(&checked, &profile);
+  // The separator can be replaced by any of the 36 characters.
+  assert_eq!(analysis.edits, 8 * 35 + 36 + 8);
+  assert_eq!(analysis.valid_edits, 0);
+}
+
+#[test]
+fn lookup_table() {
+  assert_eq!(
+    TableFilter::parse("pen=0,bas=1-2,mov=2-,din=-1"),
+    Ok(
+      TableFilter::default()
+        .with_range(Category::Pen, 0, 0)
+        .with_range(Category::Bas, 1, 2)
+        .with_range(Category::Mov, 2, u32::MAX)
+        .with_range(Category::Din, 0, 1)
+    )
+  );
+  assert_eq!(
+    TableFilter::parse("pen"),
+    Err(JudgementError::MalformedEntry("pen".to_string()))
+  );
+  assert_eq!(
+    TableFilter::parse("foo=1"),
+    Err(JudgementError::UnknownCategory("foo".to_string()))
+  );
+  for value in &["", "-", "x", "1-x", "1-2-3"] {
+    assert_eq!(
+      TableFilter::parse(&format!("bas={}", value)),
+      Err(JudgementError::InvalidValue {
+        category: Category::Bas,
+        value: value.to_string()
+      })
+    );
+  }
+
+  let profile = ScoringProfile::default();
+  let filter = TableFilter::parse("pen=0")
+    .unwrap()
+    .with_layout(Layout::One);
+  let records: Vec<Record> = table(&profile, &filter).collect();
+  assert_eq!(
+    records.len(),
+    StyleCode::all_valid()
+      .filter(|style| style.get(Category::Pen) == 0 && style.layout() == Layout::One)
+      .count()
+  );
+  assert_eq!(records[0].code, "a0");
+  for record in &records {
+    assert_eq!(record.layout, Layout::One);
+    assert_eq!(record.style.get(Category::Pen), 0);
+    assert_eq!(StyleCode::decode(&record.code), Ok(record.style));
+    assert_eq!(record.score, record.style.score());
+  }
+
+  // Judgements rejected by the score policy are left out.
+  let strict = profile.with_score_policy(ScorePolicy::Reject(Score::from_tenths(60)));
+  assert!(table(&strict, &filter).all(|record| record.score >= Score::from_tenths(60)));
+  assert!(table(&strict, &filter).count() < records.len());
+}
+
+#[test]
+fn reverse_search() {
+  let parse = |constraints: &[&str]| -> Result<Vec<Constraint>, SearchError> {
+    constraints.iter().map(|c| c.parse()).collect()
+  };
+  assert_eq!(
+    "mov".parse::<Constraint>(),
+    Err(SearchError::MalformedConstraint("mov".to_string()))
+  );
+  assert_eq!(
+    "mov>=".parse::<Constraint>(),
+    Err(SearchError::MalformedConstraint("mov>=".to_string()))
+  );
+  assert_eq!(
+    "foo=1".parse::<Constraint>(),
+    Err(SearchError::UnknownField("foo".to_string()))
+  );
+  assert_eq!(
+    "code>g1".parse::<Constraint>(),
+    Err(SearchError::InvalidOperator {
+      field: "code".to_string(),
+      operator: ">".to_string()
+    })
+  );
+  assert_eq!(
+    "score=7.55".parse::<Constraint>(),
+    Err(SearchError::InvalidValue {
+      field: "score".to_string(),
+      value: "7.55".to_string()
+    })
+  );
+
+  let profile = ScoringProfile::default();
+  let results = Search::new(parse(&["score=7.5", "pen=0", "mov>=2"]).unwrap()).run(&profile);
+  assert!(!results.is_empty());
+  for record in &results {
+    assert_eq!(record.score, Score::from_tenths(75));
+    assert_eq!(record.style.get(Category::Pen), 0);
+    assert!(record.style.get(Category::Mov) >= 2);
+    assert_eq!(record.code, record.style.encode());
+  }
+
+  // Sorting and limits.
+  let results = Search::new(parse(&["code=g1?*", "sog!=0", "dif < 2"]).unwrap())
+    .with_sort(Field::Score, true)
+    .with_limit(10)
+    .run(&profile);
+  assert_eq!(results.len(), 10);
+  assert!(results
+    .windows(2)
+    .all(|pair| pair[0].score >= pair[1].score));
+  for record in &results {
+    assert!(record.code.starts_with("g1") && record.code.len() >= 3);
+    assert_ne!(record.style.get(Category::Sog), 0);
+    assert!(record.style.get(Category::Dif) < 2);
+  }
+
+  assert_eq!(
+    Search::new(parse(&["code=n4"]).unwrap())
+      .run(&profile)
+      .into_iter()
+      .map(|record| record.style)
+      .collect::<Vec<_>>(),
+    vec![StyleCode::decode("n4").unwrap()]
+  );
+  assert!(Search::new(parse(&["pen<0"]).unwrap())
+    .run(&profile)
+    .is_empty());
+}
+
+#[test]
+fn target_scores() {
+  let profile = ScoringProfile::default();
+  let style = StyleCode::decode("n4").unwrap();
+  let plans = reach_target(&style, Score::from_tenths(70), &profile);
+  assert!(!plans.is_empty());
+  assert!(plans
+    .windows(2)
+    .all(|pair| pair[0].points() <= pair[1].points()));
+  // 0.7 needs at least four style points, or three and a SOG point.
+  assert_eq!(plans[0].points(), 4);
+  assert_eq!(plans[0].changes.len(), 2);
+  for plan in &plans {
+    assert!(plan.score >= Score::from_tenths(70));
+    assert!(plan.changes.iter().all(|change| change.to > change.from));
+    for change in &plan.changes {
+      assert_eq!(plan.style.get(change.category), change.to);
+      assert_eq!(style.get(change.category), change.from);
+    }
+  }
+  assert!(plans.iter().any(|plan| plan.changes
+    == vec![
+      CategoryChange {
+        category: Category::Bas,
+        from: 1,
+        to: 3
+      },
+      CategoryChange {
+        category: Category::Mov,
+        from: 1,
+        to: 3
+      },
+    ]));
+
+  // Penalties are removed rather than added.
+  let style = StyleCode::decode("g13dm12").unwrap();
+  let plans = reach_target(&style, Score::from_tenths(82), &profile);
+  assert_eq!(
+    plans[0].changes,
+    vec![CategoryChange {
+      category: Category::Pen,
+      from: 2,
+      to: 0
+    }]
+  );
+
+  let plans = reach_target(&style, Score::from_tenths(50), &profile);
+  assert_eq!(plans.len(), 1);
+  assert!(plans[0].changes.is_empty());
+  assert!(reach_target(&style, Score::from_tenths(200), &profile).is_empty());
+}
+
+#[test]
+fn adjustments() {
+  let style = StyleCode::decode("g13dm12").unwrap();
+  let adjusted = style
+    .adjust(&[(Category::Pen, 1), (Category::Mov, -1)])
+    .unwrap();
+  assert_eq!(adjusted.pen, 3);
+  assert_eq!(adjusted.mov, 2);
+  assert_eq!(adjusted.encode(), "e12lc13");
+  assert_eq!(style.adjust(&[]), Ok(style));
+  // Deltas of the same category add up.
+  assert_eq!(
+    style.adjust(&[(Category::Bas, 2), (Category::Bas, -1)]),
+    style.adjust(&[(Category::Bas, 1)])
+  );
+
+  assert_eq!(
+    style.adjust(&[(Category::Pen, -3)]),
+    Err(EncodeError::Negative {
+      category: Category::Pen,
+      value: -1
+    })
+  );
+  assert_eq!(
+    style.adjust(&[(Category::Mov, 1)]),
+    Err(EncodeError::OutOfRange {
+      category: Category::Mov,
+      value: 4,
+      max: 3
+    })
+  );
+}
+
+#[test]
+fn panel_aggregation() {
+  let panel = |judgements: &[&str]| -> Vec<StyleCode> {
+    judgements
+      .iter()
+      .map(|judgement| parse_judgement(judgement).unwrap())
+      .collect()
+  };
+  let judgements = panel(&["bas=1,mov=3,pen=2", "bas=2,mov=2,pen=3", "bas=2,pen=0"]);
+
+  let mean = aggregate(&judgements, AggregationMethod::Mean).unwrap();
+  // Every category has a mean of 5/3, which rounds to 2.
+  assert_eq!(mean, parse_judgement("bas=2,mov=2,pen=2").unwrap());
+  let median = aggregate(&judgements, AggregationMethod::Median).unwrap();
+  assert_eq!(median, parse_judgement("bas=2,mov=2,pen=2").unwrap());
+  let trimmed = aggregate(&judgements, AggregationMethod::TrimmedMean).unwrap();
+  assert_eq!(trimmed, parse_judgement("bas=2,mov=2,pen=2").unwrap());
+
+  // The median of an even panel is the mean of the middle values, rounded
+  // half up; the trimmed mean ignores the outliers.
+  let judgements = panel(&["mov=0", "mov=1", "mov=2", "mov=3", "pen=20"]);
+  assert_eq!(
+    aggregate(&judgements[..4], AggregationMethod::Median),
+    Ok(parse_judgement("mov=2").unwrap())
+  );
+  assert_eq!(
+    aggregate(&judgements, AggregationMethod::Mean),
+    Ok(parse_judgement("mov=1,pen=4").unwrap())
+  );
+  assert_eq!(
+    aggregate(&judgements, AggregationMethod::TrimmedMean),
+    Ok(parse_judgement("mov=1").unwrap())
+  );
+
+  assert_eq!(
+    aggregate(&judgements[..2], AggregationMethod::TrimmedMean),
+    Err(PanelError::TooFewJudges {
+      method: AggregationMethod::TrimmedMean,
+      judges: 2,
+      minimum: 3
+    })
+  );
+  assert!(aggregate(&[], AggregationMethod::Mean).is_err());
+  for name in AggregationMethod::NAMES.iter() {
+    assert_eq!(
+      name.parse::<AggregationMethod>().unwrap().to_string(),
+      *name
+    );
+  }
+}
+
+#[test]
+fn judge_agreement() {
+  let profile = ScoringProfile::default();
+  // A table of values, one row per performance, as the values of one category.
+  let panels = |category: Category, rows: &[Vec<u32>]| -> Vec<Vec<StyleCode>> {
+    rows
+      .iter()
+      .map(|row| {
+        row
+          .iter()
+          .map(|&value| {
+            let mut style = StyleCode::default();
+            *style.get_mut(category) = value;
+            style
+          })
+          .collect()
+      })
+      .collect()
+  };
+  let close = |a: f64, b: f64| (a - b).abs() < 0.001;
+
+  // Cohen's kappa: 20 yes/yes, 5 yes/no, 10 no/yes, 15 no/no.
+  let mut rows = Vec::new();
+  rows.extend(vec![vec![1, 1]; 20]);
+  rows.extend(vec![vec![1, 0]; 5]);
+  rows.extend(vec![vec![0, 1]; 10]);
+  rows.extend(vec![vec![0, 0]; 15]);
+  let report = measure_agreement(&panels(Category::Bas, &rows), &profile).unwrap();
+  let (category, bas) = &report.categories[0];
+  assert_eq!(*category, Category::Bas);
+  assert!(close(bas.exact, 0.7));
+  assert!(close(bas.mean_absolute_difference, 0.3));
+  assert!(close(bas.cohen_kappa.unwrap(), 0.4));
+  // The first judge says yes 5 fewer times out of 50 than the second.
+  assert!(close(report.bias[0].categories[0].1, -0.05));
+  assert!(close(report.bias[1].categories[0].1, 0.05));
+  // Each BAS point is worth 0.2.
+  assert!(close(report.bias[0].score, -0.01));
+  // Nobody ever gives any MOV: the kappas are undefined.
+  let (_, mov) = &report.categories[1];
+  assert!(close(mov.exact, 1.0));
+  assert_eq!(mov.cohen_kappa, None);
+  assert_eq!(mov.fleiss_kappa, None);
+  assert_eq!(mov.icc, None);
+  let json = serde_json::to_string(&report).unwrap();
+  assert!(
+    json.starts_with("{\"performances\":50,\"judges\":2,\"categories\":{\"bas\":{\"exact\":0.7,")
+  );
+  assert!(
+    json.contains("\"mov\":{\"exact\":1.0,\"mean_absolute_difference\":0.0,\"cohen_kappa\":null,")
+  );
+  assert!(json.contains("\"bias\":[{\"categories\":{\"bas\":"));
+
+  // Fleiss' kappa: 14 judges, the number of judges choosing each of five
+  // values for 10 performances.
+  let counts = [
+    [0, 0, 0, 0, 14],
+    [0, 2, 6, 4, 2],
+    [0, 0, 3, 5, 6],
+    [0, 3, 9, 2, 0],
+    [2, 2, 8, 1, 1],
+    [7, 7, 0, 0, 0],
+    [3, 2, 6, 3, 0],
+    [2, 5, 3, 2, 2],
+    [6, 5, 2, 1, 0],
+    [0, 2, 2, 3, 7],
+  ];
+  let rows: Vec<Vec<u32>> = counts
+    .iter()
+    .map(|counts| {
+      (0..5)
+        .flat_map(|value| vec![value as u32; counts[value]])
+        .collect()
+    })
+    .collect();
+  let report = measure_agreement(&panels(Category::Pen, &rows), &profile).unwrap();
+  assert_eq!(report.judges, 14);
+  assert!(close(report.categories[8].1.fleiss_kappa.unwrap(), 0.210));
+
+  // ICC(2,1): 6 performances, 4 judges.
+  let rows = vec![
+    vec![9, 2, 5, 8],
+    vec![6, 1, 3, 2],
+    vec![8, 4, 6, 8],
+    vec![7, 1, 2, 6],
+    vec![10, 5, 6, 9],
+    vec![6, 2, 4, 7],
+  ];
+  let report = measure_agreement(&panels(Category::Pen, &rows), &profile).unwrap();
+  assert!(close(report.categories[8].1.icc.unwrap(), 0.290));
+  // Scores are not raised to the floor, so the ICC of the score is the same.
+  assert!(close(report.score.icc.unwrap(), 0.290));
+
+  assert_eq!(
+    measure_agreement(&[], &profile),
+    Err(AgreementError::NoPerformances)
+  );
+  assert_eq!(
+    measure_agreement(&[vec![StyleCode::default()]], &profile),
+    Err(AgreementError::TooFewJudges(1))
+  );
+  assert_eq!(
+    measure_agreement(
+      &[vec![StyleCode::default(); 2], vec![StyleCode::default(); 3]],
+      &profile
+    ),
+    Err(AgreementError::MissingJudgements {
+      performance: 1,
+      judgements: 3,
+      judges: 2
+    })
+  );
+
+  let file = read_panels("tests/data/panel.csv", ',', true, None, &profile).unwrap();
+  assert_eq!(file.judges, vec!["Anna", "Ben", "Carla"]);
+  assert_eq!(file.panels.len(), 5);
+  assert_eq!(file.panels[1][2], StyleCode::decode("n5").unwrap());
+  assert_eq!(file.skipped.len(), 2);
+  assert_eq!(file.skipped[0].0, 5);
+  assert!(file.skipped[0].1.starts_with("invalid code 'xx': "));
+  assert_eq!(file.skipped[1], (7, "missing column 2".to_string()));
+  let file = read_panels("tests/data/panel.csv", ',', false, Some(&[1, 2]), &profile).unwrap();
+  assert_eq!(file.judges, vec!["Judge 1", "Judge 2"]);
+  assert_eq!(file.panels.len(), 6);
+  // The headers are not codes.
+  assert_eq!(file.skipped.len(), 2);
+  assert_eq!(file.skipped[0].0, 0);
+  assert!(matches!(
+    read_panels("tests/data/missing.csv", ',', true, None, &profile),
+    Err(ProcessError::OpenInput { .. })
+  ));
+}
+
+#[test]
+fn csv_processing() {
+  let record = |fields: &[&str]| csv::StringRecord::from(fields.to_vec());
+
+  let mut processor = CsvProcessor::new(true, None);
+  let headers = processor.process_record(&record(&["Name", "Code"]));
+  assert_eq!(headers.len(), 12);
+  assert_eq!(&headers[2], "Score");
+  assert_eq!(&headers[11], "PEN");
+  let processed = processor.process_record(&record(&["AA", "n4"]));
+  assert_eq!(
+    processed.iter().collect::<Vec<_>>(),
+    vec!["AA", "n4", "6.3", "1", "1", "1", "0", "0", "1", "0", "0", "0"]
+  );
+
+  // A bad record does not stop the processing.
+  let processed = processor.process_record(&record(&["BB", "xx"]));
+  assert!(processed[2].starts_with("<invalid code: "));
+  assert_eq!(&processed[3], "<invalid code>");
+  let processed = processor.process_record(&record(&["CC"]));
+  assert_eq!(&processed[1], "<invalid code: missing column 1>");
+  assert_eq!(processed.len(), 11);
+
+  let mut processor = CsvProcessor::new(false, Some(5)).with_suggestions(true);
+  let processed = processor.process_record(&record(&["AA", "n4"]));
+  assert_eq!(&processed[2], "<invalid code: missing column 5>");
+  assert_eq!(&processed[12], "");
+  let processed = processor.process_record(&record(&[]));
+  assert_eq!(&processed[0], "<invalid code: missing column 5>");
+
+  // Normalized codes keep their check character, as with
+  // StyleCode::normalize.
+  let mut processor = CsvProcessor::new(false, Some(0)).with_normalize(true);
+  let processed = processor.process_record(&record(&["G13DM12-B", "x"]));
+  assert_eq!(&processed[0], "g13dm12-b");
+  assert_eq!(
+    StyleCode::normalize("G13DM12-B"),
+    Ok(processed[0].to_string())
+  );
+  let processed = processor.process_record(&record(&[" b2sk "]));
+  assert_eq!(&processed[0], "j2");
+
+  assert_eq!(
+    process_file("in.csv", "in.csv", ',', CsvProcessor::new(false, None)),
+    Err(ProcessError::SameFile("in.csv".to_string()))
+  );
+  assert!(matches!(
+    process_file(
+      "tests/data/missing.csv",
+      "tests/data/out.csv",
+      ',',
+      CsvProcessor::new(false, None)
+    ),
+    Err(ProcessError::OpenInput { .. })
+  ));
+}
+
+#[test]
+fn code_comparison() {
+  let record = |code: &str| {
+    let (style, layout) = StyleCode::decode_with_layout(code).unwrap();
+    Record {
+      code: code.to_string(),
+      layout,
+      style,
+      score: style.score(),
+    }
+  };
+
+  let pair = compare(&[record("g13dm12"), record("e12lc13")]).unwrap();
+  assert_eq!(pair.score_delta, Some(Score::from_tenths(-7)));
+  assert_eq!(pair.score_spread, Score::from_tenths(7));
+  assert_eq!(pair.disagreements, vec![Category::Mov, Category::Pen]);
+  let mov = &pair.categories[1];
+  assert_eq!(mov.category, Category::Mov);
+  assert_eq!(mov.values, vec![3, 2]);
+  assert_eq!(mov.delta, Some(-1));
+  assert_eq!(mov.spread, 1);
+  assert!(pair.categories[0].agrees());
+  assert_eq!(
+    serde_json::to_string(&pair).unwrap(),
+    "{\"codes\":[\"g13dm12\",\"e12lc13\"],\"scores\":[7.2,6.5],\"score_delta\":-0.7,\
+     \"score_spread\":0.7,\"categories\":{\"bas\":[1,1],\"mov\":[3,2],\"din\":[2,2],\
+     \"com\":[1,1],\"sapd\":[3,3],\"gcc\":[1,1],\"dif\":[2,2],\"sog\":[1,1],\"pen\":[2,3]},\
+     \"disagreements\":[\"mov\",\"pen\"]}"
+  );
+
+  let three = compare(&[record("g13dm12"), record("e12lc13"), record("n4")]).unwrap();
+  assert_eq!(three.score_delta, None);
+  assert_eq!(three.score_spread, Score::from_tenths(9));
+  assert_eq!(three.categories[4].delta, None);
+  assert_eq!(three.categories[4].spread, 3);
+  assert!(!serde_json::to_string(&three)
+    .unwrap()
+    .contains("score_delta"));
+
+  let same = compare(&[record("n4"), record("N4")]).unwrap();
+  assert!(same.disagreements.is_empty());
+  assert_eq!(same.score_delta, Some(Score::ZERO));
+  assert_eq!(compare(&[record("n4")]), Err(DiffError::TooFewCodes(1)));
 }